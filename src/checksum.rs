@@ -0,0 +1,383 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::{Error, PathExt};
+
+/// Extensions of the checksum files released alongside parts.
+pub const CHECKSUM_EXTS: &[&str] = &["sfv", "md5", "sha256"];
+
+/// A hash function used by a checksum file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    Crc32,
+    Md5,
+    Sha256,
+}
+
+impl Algorithm {
+    /// The algorithm used by checksum files with the extension `ext`.
+    #[must_use]
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "sfv" => Some(Self::Crc32),
+            "md5" => Some(Self::Md5),
+            "sha256" => Some(Self::Sha256),
+            _ => None,
+        }
+    }
+
+    /// The length of the algorithm's hashes in hex.
+    fn hex_len(self) -> usize {
+        match self {
+            Self::Crc32 => 8,
+            Self::Md5 => 32,
+            Self::Sha256 => 64,
+        }
+    }
+
+    fn hasher(self) -> Hasher {
+        match self {
+            Self::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            Self::Md5 => Hasher::Md5(Md5::new()),
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Crc32 => "CRC32",
+            Self::Md5 => "MD5",
+            Self::Sha256 => "SHA-256",
+        })
+    }
+}
+
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Crc32(hasher) => hasher.update(bytes),
+            Self::Md5(hasher) => hasher.update(bytes),
+            Self::Sha256(hasher) => hasher.update(bytes),
+        }
+    }
+
+    /// The hash of everything given to [`Hasher::update`], in lowercase hex.
+    fn finish(self) -> String {
+        match self {
+            Self::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+            Self::Md5(hasher) => to_hex(&hasher.finalize()),
+            Self::Sha256(hasher) => to_hex(&hasher.finalize()),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        // writing to a String cannot fail.
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// The expected hash of a part.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: Algorithm,
+    /// The expected hash, in lowercase hex.
+    pub expected: String,
+    /// The checksum file the hash was read from.
+    pub source: PathBuf,
+}
+
+/// A part whose hash did not match its [`Checksum`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub algorithm: Algorithm,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {} {}, got {}",
+            self.algorithm, self.expected, self.actual
+        )
+    }
+}
+
+/// The checksums of parts, keyed by file name.
+#[derive(Clone, Debug, Default)]
+pub struct Checksums {
+    by_name: HashMap<String, Checksum>,
+    files: Vec<PathBuf>,
+}
+
+impl Checksums {
+    /// Read the checksums of `parts` listed in `files`, which are `.sfv`, `.md5` or `.sha256` files.
+    ///
+    /// Files that only contain a hash are taken to be the checksum of the file they are named after,
+    /// eg. `app.7z.001.sha256`. Lines for other files are skipped, and lines that are not a checksum
+    /// or files that list none of `parts` are skipped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a file could not be read.
+    pub fn load(files: &[PathBuf], parts: &[PathBuf]) -> Result<Self, Error> {
+        let parts: Vec<String> = parts
+            .iter()
+            .filter_map(|part| Some(part.lossy_file_name()?.to_lowercase()))
+            .collect();
+
+        let mut checksums = Self::default();
+
+        for file in files {
+            let Some(algorithm) = file
+                .lossy_extension()
+                .and_then(|ext| Algorithm::from_ext(&ext))
+            else {
+                continue;
+            };
+
+            let contents =
+                fs::read_to_string(file).map_err(|err| Error::ChecksumFile(file.clone(), err))?;
+
+            let mut listed = false;
+            for (n, line) in contents.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                    continue;
+                }
+
+                let Some((name, expected)) = parse_line(algorithm, line, file) else {
+                    eprintln!(
+                        "Skipping line {} of {file:?}, which is not a {algorithm} checksum",
+                        n + 1
+                    );
+                    continue;
+                };
+
+                let name = name.to_lowercase();
+                if !parts.contains(&name) {
+                    continue;
+                }
+
+                listed = true;
+                checksums.by_name.insert(
+                    name,
+                    Checksum {
+                        algorithm,
+                        expected: expected.to_lowercase(),
+                        source: file.clone(),
+                    },
+                );
+            }
+
+            if listed {
+                checksums.files.push(file.clone());
+            } else {
+                eprintln!("Skipping {file:?}, which lists none of the parts");
+            }
+        }
+
+        Ok(checksums)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.by_name.is_empty()
+    }
+
+    /// The checksum files that list any of the parts.
+    #[must_use]
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The checksum of `part`, matched by file name ignoring case.
+    #[must_use]
+    pub fn get(&self, part: &Path) -> Option<&Checksum> {
+        self.by_name.get(&part.lossy_file_name()?.to_lowercase())
+    }
+
+    /// Hash each of `parts` that has a checksum, printing the result.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if a part could not be read, or its hash does not match.
+    pub fn verify_all(&self, parts: &[PathBuf]) -> Result<(), Error> {
+        for (n, part) in parts.iter().enumerate() {
+            let Some(checksum) = self.get(part) else {
+                continue;
+            };
+
+            println!(
+                "{}/{}: verifying {part:?} ({})",
+                n + 1,
+                parts.len(),
+                checksum.algorithm
+            );
+
+            let mut reader = self.reader(
+                part,
+                File::open(part).map_err(|err| Error::Verify(part.clone(), err))?,
+            );
+            io::copy(&mut reader, &mut io::sink())
+                .map_err(|err| Error::Verify(part.clone(), err))?;
+            reader.finish()?;
+        }
+
+        Ok(())
+    }
+
+    /// Hash the section of the archive `combined` from each of `parts`, `sizes` long, that has a checksum,
+    /// printing the result.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `combined` could not be read, or the hash of a section does not match.
+    pub fn verify_combined(
+        &self,
+        combined: &Path,
+        parts: &[PathBuf],
+        sizes: &[u64],
+    ) -> Result<(), Error> {
+        let verify_err = |err| Error::Verify(combined.to_path_buf(), err);
+        let mut file = File::open(combined).map_err(verify_err)?;
+
+        for (n, (part, &size)) in parts.iter().zip(sizes).enumerate() {
+            let Some(checksum) = self.get(part) else {
+                file.seek(SeekFrom::Current(size.try_into().unwrap_or(i64::MAX)))
+                    .map_err(verify_err)?;
+                continue;
+            };
+
+            println!(
+                "{}/{}: verifying {part:?} in {combined:?} ({})",
+                n + 1,
+                parts.len(),
+                checksum.algorithm
+            );
+
+            let mut reader = self.reader(part, (&mut file).take(size));
+            io::copy(&mut reader, &mut io::sink()).map_err(verify_err)?;
+            reader.finish()?;
+        }
+
+        Ok(())
+    }
+
+    /// Wrap `inner`, the contents of `part`, in a reader that hashes what is read from it.
+    pub fn reader<R: Read>(&self, part: &Path, inner: R) -> VerifyingReader<R> {
+        let checksum = self.get(part).cloned();
+
+        VerifyingReader {
+            inner,
+            hasher: checksum
+                .as_ref()
+                .map(|checksum| checksum.algorithm.hasher()),
+            checksum,
+            part: part.to_path_buf(),
+        }
+    }
+}
+
+/// Parse the name and hash from a `line` of the checksum file `file`.
+fn parse_line<'a>(
+    algorithm: Algorithm,
+    line: &'a str,
+    file: &'a Path,
+) -> Option<(&'a str, &'a str)> {
+    let is_hash = |hash: &str| {
+        hash.len() == algorithm.hex_len() && hash.chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    let (name, hash) = if algorithm == Algorithm::Crc32 {
+        // sfv: `name crc32`
+        let (name, hash) = line.rsplit_once(char::is_whitespace)?;
+        (name.trim_end(), hash)
+    } else if let Some((name, hash)) = line
+        .rsplit_once(" = ")
+        .and_then(|(left, hash)| Some((left.split_once('(')?.1.strip_suffix(')')?, hash)))
+    {
+        // bsd: `SHA256 (name) = hash`
+        (name, hash)
+    } else if let Some((hash, name)) = line.split_once(char::is_whitespace) {
+        // gnu: `hash  name`, or `hash *name` for binary mode.
+        let name = name.trim_start();
+        (name.strip_prefix('*').unwrap_or(name), hash)
+    } else {
+        // only a hash, for the file the checksum file is named after.
+        (file.file_stem()?.to_str()?, line)
+    };
+
+    // checksum files can list paths, parts are matched by name only.
+    let name = Path::new(name).file_name()?.to_str()?;
+
+    is_hash(hash).then_some((name, hash))
+}
+
+/// Hashes what is read through it, checking the hash against the part's [`Checksum`] when finished.
+pub struct VerifyingReader<R> {
+    inner: R,
+    hasher: Option<Hasher>,
+    checksum: Option<Checksum>,
+    part: PathBuf,
+}
+
+impl<R> VerifyingReader<R> {
+    /// Check the hash of everything read against the part's checksum.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the hash does not match.
+    pub fn finish(self) -> Result<(), Error> {
+        let (Some(hasher), Some(checksum)) = (self.hasher, self.checksum) else {
+            return Ok(());
+        };
+
+        let actual = hasher.finish();
+        if actual == checksum.expected {
+            return Ok(());
+        }
+
+        Err(Error::Checksum(
+            self.part,
+            Mismatch {
+                algorithm: checksum.algorithm,
+                expected: checksum.expected,
+                actual,
+            },
+        ))
+    }
+}
+
+impl<R: Read> Read for VerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..read]);
+        }
+
+        Ok(read)
+    }
+}
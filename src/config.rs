@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::Error;
+
+/// Environment variable naming the config file to use instead of the one in the config directory.
+pub const CONFIG_ENV: &str = "pinst_config";
+
+/// The per-user config file.
+///
+/// `$XDG_CONFIG_HOME/partsinstall/config.toml` on linux, `%APPDATA%\partsinstall\config.toml` on windows,
+/// or the file named by [`CONFIG_ENV`].
+#[must_use]
+pub fn config_path() -> Option<PathBuf> {
+    env::var_os(CONFIG_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::config_dir().map(|dir| dir.join("partsinstall").join("config.toml")))
+}
+
+/// Defaults for command line options, read from a config file.
+///
+/// Settings are named after the long option they set, with `-` or `_` between words,
+/// and are used when the option is not given on the command line or in the environment.
+///
+/// ```toml
+/// destination = "D:/Apps"
+/// no_shortcut = true
+/// extractor = "7z"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    /// The file the config was read from, if it exists.
+    pub path: Option<PathBuf>,
+    values: BTreeMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Read the config at `path`. A missing file is an empty config.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` could not be read, or is not valid TOML,
+    /// or has a setting that is a table.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(Error::Config(path.to_path_buf(), err)),
+        };
+
+        let invalid = |msg: String| {
+            Error::Config(
+                path.to_path_buf(),
+                io::Error::new(io::ErrorKind::InvalidData, msg),
+            )
+        };
+
+        let table: toml::Table =
+            toml::from_str(&contents).map_err(|err| invalid(err.to_string()))?;
+
+        let mut values = BTreeMap::new();
+        for (key, value) in table {
+            let value = match value {
+                toml::Value::Array(items) => items.into_iter().map(to_arg).collect(),
+                value => to_arg(value).map(|value| vec![value]),
+            }
+            .ok_or_else(|| invalid(format!("`{key}` is not a string, number, boolean or list")))?;
+
+            values.insert(key.replace('-', "_"), value);
+        }
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            values,
+        })
+    }
+
+    /// The values of the setting `key`, as they would be given on the command line.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&[String]> {
+        self.values.get(key).map(Vec::as_slice)
+    }
+
+    /// Every setting, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.values
+            .iter()
+            .map(|(key, values)| (key.as_str(), values.as_slice()))
+    }
+}
+
+/// A TOML value as a command line argument, if it is not a table or list.
+fn to_arg(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value),
+        toml::Value::Integer(value) => Some(value.to_string()),
+        toml::Value::Float(value) => Some(value.to_string()),
+        toml::Value::Boolean(value) => Some(value.to_string()),
+        toml::Value::Datetime(value) => Some(value.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
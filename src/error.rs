@@ -0,0 +1,208 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::{checksum::Mismatch, extract::ExtractError};
+
+/// Errors that can stop an install.
+#[derive(Debug)]
+pub enum Error {
+    /// The destination of the install does not exist.
+    MissingDestination(PathBuf),
+    /// The working directory could not be changed to.
+    WorkingDir(PathBuf, io::Error),
+    /// The app name could not be parsed from the name given.
+    AppName(PathBuf),
+    /// The glob pattern made from the app name was not valid.
+    Pattern(glob::PatternError),
+    /// No files were found starting with the app name.
+    NoFilesFound(String),
+    /// A file found alongside numbered parts is not numbered itself.
+    NotNumbered(PathBuf),
+    /// The parts' numbers are zero-padded to different widths, eg. `.001` and `.01`.
+    MixedPadding(Vec<PathBuf>),
+    /// More than one part has the same number.
+    DuplicateParts(Vec<PathBuf>),
+    /// Numbers are missing from the parts' sequence.
+    MissingParts(Vec<u32>),
+    /// The extension of the combined archive could not be determined.
+    UnknownExtension,
+    /// The user answered no to a prompt.
+    Cancelled,
+    /// Reading the user's answer to a prompt failed.
+    Prompt(io::Error),
+    /// Combining the parts into one archive failed.
+    Combine(PathBuf, io::Error),
+    /// A checksum file could not be read.
+    ChecksumFile(PathBuf, io::Error),
+    /// A part did not match its checksum.
+    Checksum(PathBuf, Mismatch),
+    /// A part could not be read to verify it.
+    Verify(PathBuf, io::Error),
+    /// The destination folder could not be created or read.
+    Destination(PathBuf, io::Error),
+    /// None of the installed extractors support the archive.
+    NoExtractor(PathBuf),
+    /// None of the installed extractors can stream the archive's format.
+    NoStreamExtractor(String),
+    /// The extractor failed.
+    Extract(ExtractError),
+    /// The file the archive's password was to be read from could not be read.
+    PasswordFile(PathBuf, io::Error),
+    /// Moving the contents of an inner directory failed.
+    Flatten(PathBuf, io::Error),
+    /// Creating the shortcut failed.
+    Shortcut(String),
+    /// Reading or writing an install manifest failed.
+    Manifest(PathBuf, io::Error),
+    /// A post-install command of the app's profile could not be run or failed.
+    Hook(String, io::Error),
+    /// No manifest was found for the app, so it was not installed by this tool.
+    NotInstalled(String),
+    /// A manifest pointed at a path that uninstalling is not allowed to remove.
+    OutsideDestination(PathBuf),
+    /// Removing an installed file or shortcut failed.
+    Uninstall(PathBuf, io::Error),
+    /// The file listing the apps to install could not be read.
+    AppList(PathBuf, io::Error),
+    /// Installing some of a batch of apps failed, with the number that failed out of the total.
+    Batch(usize, usize),
+    /// The app's profile could not be read or is not valid.
+    Profile(PathBuf, io::Error),
+    /// The config file could not be read or is not valid.
+    Config(PathBuf, io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingDestination(path) => write!(f, "Destination {path:?} does not exist."),
+            Self::WorkingDir(path, err) => {
+                write!(f, "Could not use working directory {path:?}: {err}")
+            }
+            Self::AppName(name) => write!(f, "Could not parse app name from {name:?}."),
+            Self::Pattern(err) => write!(f, "Glob pattern was not valid: {err}"),
+            Self::NoFilesFound(name) => {
+                write!(f, "No files were found starting with the name {name}")
+            }
+            Self::NotNumbered(path) => write!(
+                f,
+                "{path:?} was found with the parts but is not numbered, move it out of the way."
+            ),
+            Self::MixedPadding(paths) => write!(
+                f,
+                "These parts are numbered with a different zero-padding than the first part: {paths:?}"
+            ),
+            Self::DuplicateParts(paths) => {
+                write!(f, "These parts have the same number: {paths:?}")
+            }
+            Self::MissingParts(numbers) => {
+                write!(f, "Parts are missing, download part(s) ")?;
+                write_ranges(f, numbers)
+            }
+            Self::UnknownExtension => write!(f, "Could not determine output file extension"),
+            Self::Cancelled => write!(f, "Cancelled."),
+            Self::Prompt(err) => write!(f, "Failed to read stdin: {err}"),
+            Self::Combine(path, err) => write!(f, "Could not combine {path:?}: {err}"),
+            Self::ChecksumFile(path, err) => {
+                write!(f, "Could not read checksum file {path:?}: {err}")
+            }
+            Self::Checksum(path, mismatch) => write!(
+                f,
+                "Part {path:?} is corrupt or incomplete, download it again ({mismatch})."
+            ),
+            Self::Verify(path, err) => write!(f, "Could not verify {path:?}: {err}"),
+            Self::Destination(path, err) => {
+                write!(f, "Could not create destination folder {path:?}: {err}")
+            }
+            Self::NoExtractor(archive) => write!(
+                f,
+                "None of the installed extractors support {archive:?}, is 7z installed?"
+            ),
+            Self::NoStreamExtractor(format) => write!(
+                f,
+                "None of the installed extractors can stream .{format} archives, try without --stream"
+            ),
+            Self::Extract(err) => err.fmt(f),
+            Self::PasswordFile(path, err) => {
+                write!(f, "Could not read password from {path:?}: {err}")
+            }
+            Self::Flatten(path, err) => write!(f, "Could not flatten {path:?}: {err}"),
+            Self::Shortcut(reason) => write!(f, "Could not create shortcut: {reason}"),
+            Self::Manifest(path, err) => write!(f, "Could not use install manifest {path:?}: {err}"),
+            Self::Hook(command, err) => write!(f, "Post-install command `{command}` failed: {err}"),
+            Self::NotInstalled(name) => write!(f, "{name} was not installed by partsinstall."),
+            Self::OutsideDestination(path) => write!(
+                f,
+                "Refusing to remove {path:?}, it is outside of the recorded install."
+            ),
+            Self::Uninstall(path, err) => write!(f, "Could not remove {path:?}: {err}"),
+            Self::AppList(path, err) => {
+                write!(f, "Could not read the apps to install from {path:?}: {err}")
+            }
+            Self::Batch(failed, total) => write!(f, "{failed} of {total} apps failed to install."),
+            Self::Profile(path, err) => write!(f, "Could not use profile {path:?}: {err}"),
+            Self::Config(path, err) => write!(f, "Could not use config {path:?}: {err}"),
+        }
+    }
+}
+
+/// Write sorted `numbers` collapsing runs into ranges, eg. `3, 5-7`.
+fn write_ranges(f: &mut fmt::Formatter<'_>, numbers: &[u32]) -> fmt::Result {
+    let mut numbers = numbers.iter().copied().peekable();
+    let mut first = true;
+
+    while let Some(start) = numbers.next() {
+        let mut end = start;
+        while numbers.next_if_eq(&(end + 1)).is_some() {
+            end += 1;
+        }
+
+        if !first {
+            f.write_str(", ")?;
+        }
+        first = false;
+
+        if start == end {
+            write!(f, "{start}")?;
+        } else {
+            write!(f, "{start}-{end}")?;
+        }
+    }
+
+    Ok(())
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::WorkingDir(_, err)
+            | Self::Prompt(err)
+            | Self::Combine(_, err)
+            | Self::ChecksumFile(_, err)
+            | Self::Verify(_, err)
+            | Self::Destination(_, err)
+            | Self::PasswordFile(_, err)
+            | Self::Flatten(_, err)
+            | Self::Manifest(_, err)
+            | Self::Hook(_, err)
+            | Self::Uninstall(_, err)
+            | Self::AppList(_, err)
+            | Self::Profile(_, err)
+            | Self::Config(_, err) => Some(err),
+            Self::Pattern(err) => Some(err),
+            Self::Extract(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ExtractError> for Error {
+    fn from(err: ExtractError) -> Self {
+        Self::Extract(err)
+    }
+}
+
+impl From<glob::PatternError> for Error {
+    fn from(err: glob::PatternError) -> Self {
+        Self::Pattern(err)
+    }
+}
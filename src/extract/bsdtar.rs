@@ -0,0 +1,85 @@
+use std::{ffi::OsString, path::Path};
+
+use super::{Answers, ExternalExtractor, ExtractError};
+
+/// Extract with `bsdtar` from [libarchive](https://libarchive.org/).
+///
+/// Windows ships it as `tar`.
+pub struct Bsdtar;
+
+impl ExternalExtractor for Bsdtar {
+    fn program(&self) -> &'static str {
+        if cfg!(windows) {
+            "tar"
+        } else {
+            "bsdtar"
+        }
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        &["tar", "tgz", "gz", "xz", "bz2", "zip", "7z", "rar"]
+    }
+
+    fn args(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        // bsdtar only prompts for a missing passphrase, and overwrites existing files by default.
+        let mut args = vec![
+            "-xf".into(),
+            archive.into(),
+            "-C".into(),
+            destination.into(),
+        ];
+        if answers == Answers::No {
+            // -k - keep existing files
+            args.push("-k".into());
+        }
+        args.extend(passphrase_args(password));
+
+        args
+    }
+
+    fn stream_formats(&self) -> &'static [&'static str] {
+        // 7z needs seeking, which a pipe cannot do.
+        &["tar", "tgz", "gz", "xz", "bz2", "zip", "rar"]
+    }
+
+    fn stream_args(
+        &self,
+        _format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        // bsdtar detects the format itself, - means stdin.
+        let mut args = vec!["-xf".into(), "-".into(), "-C".into(), destination.into()];
+        args.extend(passphrase_args(password));
+
+        args
+    }
+
+    fn is_wrong_password(&self, errors: &str) -> bool {
+        // eg. `Incorrect passphrase`, `Passphrase required for this entry`
+        errors.to_lowercase().contains("passphrase")
+    }
+
+    fn map_exit_code(&self, code: i32) -> Result<(), ExtractError> {
+        match code {
+            0 => Ok(()),
+            1 => Err(ExtractError::Fatal(self.program())),
+            code => Err(ExtractError::UnknownExitCode(self.program(), code)),
+        }
+    }
+}
+
+/// Arguments giving bsdtar `password`, if any.
+///
+/// An empty passphrase is rejected by libarchive, so without a password none is given.
+fn passphrase_args(password: Option<&str>) -> Vec<OsString> {
+    password
+        .map(|password| vec!["--passphrase".into(), password.into()])
+        .unwrap_or_default()
+}
@@ -0,0 +1,520 @@
+mod bsdtar;
+#[cfg(feature = "native")]
+mod native;
+mod sevenzip;
+mod unar;
+mod unrar;
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt,
+    io::{self, BufRead, BufReader, Read, Seek},
+    path::Path,
+    process::{Child, ChildStderr, ChildStdout, Command, Stdio},
+    thread::{self, JoinHandle},
+};
+
+pub use bsdtar::Bsdtar;
+#[cfg(feature = "native")]
+pub use native::{ArchiveEntry, Native};
+pub use sevenzip::SevenZip;
+pub use unar::Unar;
+pub use unrar::Unrar;
+
+use crate::{progress::Progress, volumes::VolumeKind, PathExt};
+
+/// How an extractor answers its own prompts, like whether to overwrite an existing file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Answers {
+    /// Let the user answer.
+    Ask,
+    /// Answer yes without prompting, overwriting existing files.
+    Yes,
+    /// Answer no without prompting, keeping existing files.
+    No,
+}
+
+/// Something that can extract archives.
+pub trait Extractor {
+    /// Name of the extractor, as shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// Lowercase archive extensions this extractor can handle.
+    fn formats(&self) -> &'static [&'static str];
+
+    /// Returns true if the extractor can be used on this machine.
+    fn is_available(&self) -> bool;
+
+    /// Extract `archive` into the existing directory `destination`.
+    ///
+    /// Unless `answers` is [`Answers::Ask`], the extractor must not prompt and answers as told instead.
+    /// Encrypted archives are decrypted with `password`, the extractor never asks for one itself.
+    /// Extractors that can tell how far they are report it to `progress`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the extractor could not be run or failed to extract `archive`,
+    /// [`ExtractError::WrongPassword`] if `password` is wrong or missing.
+    fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+        progress: &Progress,
+    ) -> Result<(), ExtractError>;
+
+    /// Lowercase archive extensions this extractor can read from a stream.
+    fn stream_formats(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Multi-volume sets this extractor can extract when given the first volume.
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        &[]
+    }
+
+    /// Extract an archive with the extension `format`, read from `archive`, into the existing directory `destination`.
+    ///
+    /// Nothing can be prompted for while streaming, so existing files are always overwritten.
+    /// Progress is counted by whoever reads `archive`, eg. with [`Progress::reader`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `format` is not in [`Extractor::stream_formats`],
+    /// or the extractor could not be run or failed to extract `archive`.
+    fn extract_stream(
+        &self,
+        archive: Box<dyn ReadSeek>,
+        format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<(), ExtractError> {
+        let _ = (archive, destination, password);
+        Err(ExtractError::Unsupported(self.name(), format.to_string()))
+    }
+
+    /// Returns true if the extension of `archive` is in [`Extractor::formats`].
+    fn supports(&self, archive: &Path) -> bool {
+        archive
+            .lossy_extension()
+            .is_some_and(|ext| self.formats().contains(&ext.to_lowercase().as_ref()))
+    }
+
+    /// Returns true if `format` is in [`Extractor::stream_formats`].
+    fn supports_stream(&self, format: &str) -> bool {
+        self.stream_formats()
+            .contains(&format.to_lowercase().as_ref())
+    }
+}
+
+/// A seekable stream an archive can be read from, see [`Extractor::extract_stream`].
+pub trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// An [`Extractor`] that runs an external program.
+pub trait ExternalExtractor {
+    /// Name of the program, without any `.exe` suffix.
+    fn program(&self) -> &'static str;
+
+    /// Lowercase archive extensions this program can handle.
+    fn formats(&self) -> &'static [&'static str];
+
+    /// Arguments to pass to the program to extract `archive` into `destination`,
+    /// decrypting it with `password` if given, without ever asking for a password.
+    fn args(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+    ) -> Vec<OsString>;
+
+    /// Lowercase archive extensions this program can read from stdin.
+    fn stream_formats(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Multi-volume sets this program can extract when given the first volume.
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        &[]
+    }
+
+    /// Arguments to pass to the program to extract an archive with the extension `format`
+    /// from stdin into `destination`, decrypting it with `password` if given, without prompting.
+    fn stream_args(
+        &self,
+        format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        let _ = (format, destination, password);
+        Vec::new()
+    }
+
+    /// Arguments that make the program print its progress to stdout, see [`ExternalExtractor::parse_progress`].
+    fn progress_args(&self) -> Vec<OsString> {
+        Vec::new()
+    }
+
+    /// Parse the percentage done from a piece of the program's output.
+    fn parse_progress(&self, output: &str) -> Option<u64> {
+        let _ = output;
+        None
+    }
+
+    /// Returns true if the program's `errors`, everything it printed to stderr, say the password was wrong or missing.
+    fn is_wrong_password(&self, errors: &str) -> bool {
+        let _ = errors;
+        false
+    }
+
+    /// Map the program's exit `code` to a result.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `code` means extraction failed.
+    fn map_exit_code(&self, code: i32) -> Result<(), ExtractError>;
+}
+
+impl<T: ExternalExtractor> Extractor for T {
+    fn name(&self) -> &'static str {
+        self.program()
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        ExternalExtractor::formats(self)
+    }
+
+    fn is_available(&self) -> bool {
+        in_path(self.program())
+    }
+
+    fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+        progress: &Progress,
+    ) -> Result<(), ExtractError> {
+        let progress_args = self.progress_args();
+        if progress_args.is_empty() {
+            let args = self.args(archive, destination, answers, password);
+            let running = spawn(self.program(), args, Stdio::inherit(), Stdio::inherit())?;
+            return wait(self, running);
+        }
+
+        // the program may prompt on stdout, so it is never let to when its output is parsed.
+        // it is always given the password and extracts into a fresh folder, so there is nothing to ask anyway.
+        let answers = match answers {
+            Answers::Ask => Answers::No,
+            answers => answers,
+        };
+        let mut args = self.args(archive, destination, answers, password);
+        args.extend(progress_args);
+        let mut running = spawn(self.program(), args, Stdio::null(), Stdio::piped())?;
+
+        progress.start_percent();
+        let read = running
+            .child
+            .stdout
+            .take()
+            .map(|stdout| read_progress(self, stdout, progress));
+        progress.finish();
+
+        let result = wait(self, running);
+        match read {
+            Some(Err(err)) if result.is_ok() => Err(ExtractError::Io(err)),
+            _ => result,
+        }
+    }
+
+    fn stream_formats(&self) -> &'static [&'static str] {
+        ExternalExtractor::stream_formats(self)
+    }
+
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        ExternalExtractor::volume_kinds(self)
+    }
+
+    fn extract_stream(
+        &self,
+        mut archive: Box<dyn ReadSeek>,
+        format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<(), ExtractError> {
+        if !self.supports_stream(format) {
+            return Err(ExtractError::Unsupported(
+                self.program(),
+                format.to_string(),
+            ));
+        }
+
+        let mut running = spawn(
+            self.program(),
+            self.stream_args(format, destination, password),
+            Stdio::piped(),
+            Stdio::inherit(),
+        )?;
+
+        let copied = running
+            .child
+            .stdin
+            .take()
+            .map(|mut stdin| io::copy(&mut archive, &mut stdin));
+
+        // the program may exit before reading everything, in which case its exit code explains why.
+        let result = wait(self, running);
+        match copied {
+            Some(Err(err)) if result.is_ok() => Err(ExtractError::Io(err)),
+            _ => result,
+        }
+    }
+}
+
+/// A running extractor program.
+struct Running {
+    child: Child,
+    /// Passes the program's stderr through, returning everything it printed once it closes.
+    errors: Option<JoinHandle<String>>,
+}
+
+/// Spawn `program` with `args`, mapping a missing program to [`ExtractError::NotFound`].
+fn spawn(
+    program: &'static str,
+    args: Vec<OsString>,
+    stdin: Stdio,
+    stdout: Stdio,
+) -> Result<Running, ExtractError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(stdin)
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ExtractError::NotFound(program),
+            _ => ExtractError::Io(err),
+        })?;
+
+    // read on its own thread, so a program writing lots of errors never blocks on a full pipe.
+    let errors = child
+        .stderr
+        .take()
+        .map(|stderr| thread::spawn(|| pass_errors(stderr)));
+
+    Ok(Running { child, errors })
+}
+
+/// Print everything from `stderr` line by line until it closes, returning it all.
+fn pass_errors(stderr: ChildStderr) -> String {
+    let mut errors = String::new();
+
+    for line in BufReader::new(stderr).split(b'\n').map_while(Result::ok) {
+        let line = String::from_utf8_lossy(&line);
+        eprintln!("{}", line.trim_end());
+
+        errors.push_str(&line);
+        errors.push('\n');
+    }
+
+    errors
+}
+
+/// Read `stdout` of `extractor` until it closes, reporting the progress it prints
+/// and printing everything else.
+fn read_progress(
+    extractor: &impl ExternalExtractor,
+    mut stdout: ChildStdout,
+    progress: &Progress,
+) -> io::Result<()> {
+    let mut buf = [0; 4096];
+    let mut pending = Vec::new();
+
+    loop {
+        let read = stdout.read(&mut buf)?;
+        let eof = read == 0;
+        pending.extend_from_slice(&buf[..read]);
+
+        // progress is redrawn in place with backspaces or carriage returns, other output is line by line.
+        while let Some(end) = pending
+            .iter()
+            .position(|b| matches!(b, b'\n' | b'\r' | 0x08))
+        {
+            let piece: Vec<u8> = pending.drain(..=end).collect();
+            report_output(extractor, &String::from_utf8_lossy(&piece[..end]), progress);
+        }
+
+        if eof {
+            report_output(extractor, &String::from_utf8_lossy(&pending), progress);
+            return Ok(());
+        }
+    }
+}
+
+fn report_output(extractor: &impl ExternalExtractor, output: &str, progress: &Progress) {
+    if let Some(percent) = extractor.parse_progress(output) {
+        progress.set(percent);
+    } else if !output.trim().is_empty() {
+        progress.println(output.trim_end());
+    }
+}
+
+/// Wait for `running` to exit, mapping its exit code with `extractor`.
+fn wait(extractor: &impl ExternalExtractor, mut running: Running) -> Result<(), ExtractError> {
+    let status = running.child.wait().map_err(ExtractError::Io)?;
+    let errors = running
+        .errors
+        .and_then(|errors| errors.join().ok())
+        .unwrap_or_default();
+
+    // no exit code means the process was killed by a signal.
+    let Some(code) = status.code() else {
+        return Err(ExtractError::UserStopped(extractor.program()));
+    };
+
+    // most programs exit with a generic error when the password is wrong, but say so on stderr.
+    match extractor.map_exit_code(code) {
+        Err(_) if extractor.is_wrong_password(&errors) => {
+            Err(ExtractError::WrongPassword(extractor.program()))
+        }
+        result => result,
+    }
+}
+
+/// The extractors that can be chosen from.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtractorKind {
+    #[value(name = "7z")]
+    SevenZip,
+    Bsdtar,
+    Unrar,
+    Unar,
+    #[cfg(feature = "native")]
+    Native,
+}
+
+impl ExtractorKind {
+    /// The order extractors are tried in when none is chosen.
+    pub const PROBE_ORDER: &[Self] = &[
+        Self::SevenZip,
+        Self::Bsdtar,
+        Self::Unrar,
+        Self::Unar,
+        #[cfg(feature = "native")]
+        Self::Native,
+    ];
+
+    /// Create the extractor of this kind.
+    #[must_use]
+    pub fn extractor(self) -> Box<dyn Extractor> {
+        match self {
+            Self::SevenZip => Box::new(SevenZip),
+            Self::Bsdtar => Box::new(Bsdtar),
+            Self::Unrar => Box::new(Unrar),
+            Self::Unar => Box::new(Unar),
+            #[cfg(feature = "native")]
+            Self::Native => Box::new(Native),
+        }
+    }
+}
+
+/// Find the first extractor in [`ExtractorKind::PROBE_ORDER`] that is available and supports `archive`,
+/// the first volume of a set of `volumes`.
+#[must_use]
+pub fn probe(archive: &Path, volumes: VolumeKind) -> Option<Box<dyn Extractor>> {
+    ExtractorKind::PROBE_ORDER
+        .iter()
+        .map(|kind| kind.extractor())
+        .find(|extractor| {
+            extractor.supports(archive)
+                && (!volumes.is_multi_volume() || extractor.volume_kinds().contains(&volumes))
+                && extractor.is_available()
+        })
+}
+
+/// Find the first extractor in [`ExtractorKind::PROBE_ORDER`] that is available and can stream `format`.
+#[must_use]
+pub fn probe_stream(format: &str) -> Option<Box<dyn Extractor>> {
+    ExtractorKind::PROBE_ORDER
+        .iter()
+        .map(|kind| kind.extractor())
+        .find(|extractor| extractor.supports_stream(format) && extractor.is_available())
+}
+
+/// Returns true if `program` can be found in a directory in `PATH`.
+#[must_use]
+pub fn in_path(program: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+
+    let program = format!("{program}{}", env::consts::EXE_SUFFIX);
+
+    env::split_paths(&path).any(|dir| dir.join(&program).is_file())
+}
+
+/// Errors an [`Extractor`] can return.
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The extractor's program could not be found.
+    NotFound(&'static str),
+    /// The extractor does not support the archive's format.
+    Unsupported(&'static str, String),
+    /// An I/O error occurred while extracting.
+    Io(io::Error),
+    /// The extractor encountered a fatal error.
+    Fatal(&'static str),
+    /// The archive is corrupt, such as a CRC mismatch.
+    Corrupt(&'static str),
+    /// The archive is encrypted and the password was wrong or missing.
+    WrongPassword(&'static str),
+    /// The extractor was given invalid arguments.
+    CommandLine(&'static str),
+    /// The extractor ran out of memory.
+    OutOfMemory(&'static str),
+    /// The extractor could not write the extracted files.
+    Write(&'static str),
+    /// The extractor was stopped by the user.
+    UserStopped(&'static str),
+    /// The extractor exited with a code we do not know the meaning of.
+    UnknownExitCode(&'static str, i32),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "Could not run {name}, is it installed?"),
+            Self::Unsupported(name, format) => {
+                write!(f, "{name} does not support .{format} archives")
+            }
+            Self::Io(err) => write!(f, "I/O error while extracting: {err}"),
+            Self::Fatal(name) => write!(f, "{name} encountered a fatal error"),
+            Self::Corrupt(name) => write!(f, "{name}: archive is corrupt"),
+            Self::WrongPassword(name) => write!(
+                f,
+                "{name}: the archive is encrypted and the password is wrong or missing, give it with --password"
+            ),
+            Self::CommandLine(name) => write!(f, "{name}: command line error"),
+            Self::OutOfMemory(name) => write!(f, "{name}: not enough memory for operation"),
+            Self::Write(name) => write!(f, "{name}: could not write extracted files"),
+            Self::UserStopped(name) => write!(f, "{name}: user stopped the process"),
+            Self::UnknownExitCode(name, code) => {
+                write!(f, "Unknown {name} exit code {code} encountered")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
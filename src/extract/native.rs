@@ -0,0 +1,299 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Component, Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use sevenz_rust::Password;
+use zip::{result::ZipError, ZipArchive};
+
+use super::{Answers, ExtractError, Extractor, ReadSeek};
+use crate::{platform, progress::Progress, PathExt};
+
+/// Name of the built-in extractor, as shown to the user.
+const NAME: &str = "the built-in extractor";
+
+/// Built-in extractor, so no external program has to be installed.
+pub struct Native;
+
+/// A file or directory in an archive, as listed by [`Native::list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// The path of the entry, relative to where the archive is extracted.
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// The unix permissions the entry is extracted with, if the archive records them.
+    pub mode: Option<u32>,
+}
+
+impl Native {
+    /// List the entries of `archive`, a `format` archive, without extracting it.
+    /// An archive whose index is encrypted is decrypted with `password`.
+    ///
+    /// Returns `Ok(None)` for formats that can only be listed by decompressing all of them, like `tgz`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `archive` could not be read, or its index is encrypted and the password is wrong or missing.
+    pub fn list(
+        &self,
+        archive: impl Read + Seek,
+        format: &str,
+        password: Option<&str>,
+    ) -> Result<Option<Vec<ArchiveEntry>>, ExtractError> {
+        match format.to_lowercase().as_str() {
+            "zip" => list_zip(archive).map(Some),
+            "tar" => list_tar(archive).map(Some).map_err(ExtractError::Io),
+            "7z" => list_7z(archive, password).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Extractor for Native {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        &["7z", "zip", "tar", "tgz", "gz"]
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Extract `archive`, choosing the format from its extension.
+    ///
+    /// Existing files are always overwritten, since there is nothing to prompt about.
+    fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        _answers: Answers,
+        password: Option<&str>,
+        progress: &Progress,
+    ) -> Result<(), ExtractError> {
+        let ext = archive
+            .lossy_extension()
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        if !self.formats().contains(&ext.as_str()) {
+            return Err(ExtractError::Unsupported(self.name(), ext));
+        }
+
+        let file = File::open(archive).map_err(ExtractError::Io)?;
+        let size = file
+            .metadata()
+            .map_or(0, |metadata| platform::file_size(&metadata));
+
+        progress.start_bytes(size, 0);
+        let result = self.extract_stream(
+            Box::new(BufReader::new(progress.reader(file))),
+            &ext,
+            destination,
+            password,
+        );
+        progress.finish();
+
+        result
+    }
+
+    fn stream_formats(&self) -> &'static [&'static str] {
+        // we get a seekable stream, so every format works.
+        self.formats()
+    }
+
+    fn extract_stream(
+        &self,
+        archive: Box<dyn ReadSeek>,
+        format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<(), ExtractError> {
+        match format.to_lowercase().as_str() {
+            "zip" => extract_zip(archive, destination, password),
+            "tar" => extract_tar(archive, destination).map_err(ExtractError::Io),
+            "tgz" | "gz" => {
+                extract_tar(GzDecoder::new(archive), destination).map_err(ExtractError::Io)
+            }
+            "7z" => extract_7z(archive, destination, password),
+            format => Err(ExtractError::Unsupported(self.name(), format.to_string())),
+        }
+    }
+}
+
+fn extract_zip(
+    reader: impl Read + Seek,
+    destination: &Path,
+    password: Option<&str>,
+) -> Result<(), ExtractError> {
+    let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+
+    match password {
+        Some(password) => extract_zip_decrypted(&mut archive, destination, password.as_bytes()),
+        None => archive.extract(destination),
+    }
+    .map_err(zip_error)
+}
+
+/// Extract every file of `archive`, decrypting the encrypted ones with `password`.
+///
+/// [`ZipArchive::extract`] cannot decrypt, so this does what it does for regular files and directories.
+fn extract_zip_decrypted(
+    archive: &mut ZipArchive<impl Read + Seek>,
+    destination: &Path,
+    password: &[u8],
+) -> Result<(), ZipError> {
+    for n in 0..archive.len() {
+        let mut file = archive.by_index_decrypt(n, password)?;
+
+        let Some(name) = file.enclosed_name() else {
+            return Err(ZipError::Io(io::Error::other(format!(
+                "Archive entry {:?} would be extracted outside of the destination",
+                file.name()
+            ))));
+        };
+        let path = destination.join(name);
+
+        if file.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut file, &mut File::create(&path)?)?;
+
+        // keep executables executable, so a shortcut can be made to them.
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn list_zip(reader: impl Read + Seek) -> Result<Vec<ArchiveEntry>, ExtractError> {
+    let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+
+    (0..archive.len())
+        .map(|n| {
+            // raw, so encrypted entries are listed without the password.
+            let file = archive.by_index_raw(n).map_err(zip_error)?;
+
+            Ok(ArchiveEntry {
+                path: file
+                    .enclosed_name()
+                    .unwrap_or_else(|| PathBuf::from(file.name())),
+                is_dir: file.is_dir(),
+                mode: file.unix_mode(),
+            })
+        })
+        .collect()
+}
+
+/// Map `err` to [`ExtractError::WrongPassword`] if it is about the password.
+fn zip_error(err: ZipError) -> ExtractError {
+    match err {
+        ZipError::InvalidPassword => ExtractError::WrongPassword(NAME),
+        ZipError::UnsupportedArchive(reason) if reason == ZipError::PASSWORD_REQUIRED => {
+            ExtractError::WrongPassword(NAME)
+        }
+        err => ExtractError::Io(io::Error::other(err)),
+    }
+}
+
+fn extract_tar(reader: impl Read, destination: &Path) -> io::Result<()> {
+    tar::Archive::new(reader).unpack(destination)
+}
+
+fn list_tar(reader: impl Read + Seek) -> io::Result<Vec<ArchiveEntry>> {
+    let mut archive = tar::Archive::new(reader);
+
+    // seeking skips over the contents of entries instead of reading them.
+    archive
+        .entries_with_seek()?
+        .map(|entry| {
+            let entry = entry?;
+
+            Ok(ArchiveEntry {
+                path: entry.path()?.into_owned(),
+                is_dir: entry.header().entry_type().is_dir(),
+                mode: entry.header().mode().ok(),
+            })
+        })
+        .collect()
+}
+
+fn extract_7z(
+    reader: impl Read + Seek,
+    destination: &Path,
+    password: Option<&str>,
+) -> Result<(), ExtractError> {
+    let password = password.map_or_else(Password::empty, Password::from);
+
+    sevenz_rust::decompress_with_extract_fn_and_password(
+        reader,
+        destination,
+        password,
+        |entry, reader, dest| {
+            // unlike zip and tar, sevenz_rust does not check entry names for us.
+            if !is_enclosed(Path::new(entry.name())) {
+                return Err(sevenz_rust::Error::other(format!(
+                    "Archive entry {:?} would be extracted outside of the destination",
+                    entry.name()
+                )));
+            }
+
+            sevenz_rust::default_entry_extract_fn(entry, reader, dest)
+        },
+    )
+    .map_err(sevenz_error)
+}
+
+fn list_7z(
+    mut reader: impl Read + Seek,
+    password: Option<&str>,
+) -> Result<Vec<ArchiveEntry>, ExtractError> {
+    let password = password.map_or_else(Password::empty, Password::from);
+
+    let len = reader.seek(SeekFrom::End(0)).map_err(ExtractError::Io)?;
+    reader.rewind().map_err(ExtractError::Io)?;
+    let reader = sevenz_rust::SevenZReader::new(reader, len, password).map_err(sevenz_error)?;
+
+    Ok(reader
+        .archive()
+        .files
+        .iter()
+        .map(|entry| ArchiveEntry {
+            path: PathBuf::from(entry.name()),
+            is_dir: entry.is_directory(),
+            // 7z keeps unix permissions in the high bits of the windows attributes, flagged by 0x8000.
+            mode: (entry.has_windows_attributes && entry.windows_attributes() & 0x8000 != 0)
+                .then(|| entry.windows_attributes() >> 16),
+        })
+        .collect())
+}
+
+/// Map `err` to [`ExtractError::WrongPassword`] if it is about the password.
+fn sevenz_error(err: sevenz_rust::Error) -> ExtractError {
+    match err {
+        sevenz_rust::Error::PasswordRequired | sevenz_rust::Error::MaybeBadPassword(_) => {
+            ExtractError::WrongPassword(NAME)
+        }
+        err => ExtractError::Io(io::Error::other(err)),
+    }
+}
+
+/// Returns true if `path` cannot escape the directory it is joined to.
+fn is_enclosed(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
@@ -0,0 +1,116 @@
+use std::{ffi::OsString, path::Path};
+
+use super::{Answers, ExternalExtractor, ExtractError};
+use crate::volumes::VolumeKind;
+
+/// Extract with `7z` from [7-Zip](https://7-zip.org/).
+pub struct SevenZip;
+
+impl ExternalExtractor for SevenZip {
+    fn program(&self) -> &'static str {
+        "7z"
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        // https://documentation.help/7-Zip/formats.htm
+        &["7z", "zip", "rar", "tar", "tgz", "gz", "xz", "bz2"]
+    }
+
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        &[
+            VolumeKind::RarParts,
+            VolumeKind::RarLegacy,
+            VolumeKind::ZipSpanned,
+        ]
+    }
+
+    fn args(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        let mut destination_arg = OsString::from("-o");
+        destination_arg.push(destination);
+
+        // x - extract with full paths (https://documentation.help/7-Zip/extract_full.htm)
+        let mut args = vec!["x".into(), destination_arg, password_arg(password)];
+        match answers {
+            Answers::Ask => {}
+            Answers::Yes => args.push("-y".into()),
+            // -aos - skip existing files
+            Answers::No => args.push("-aos".into()),
+        }
+        args.push(archive.into());
+
+        args
+    }
+
+    fn stream_formats(&self) -> &'static [&'static str] {
+        // 7z can only read formats that do not need seeking from stdin.
+        &["tar", "tgz", "gz", "xz", "bz2"]
+    }
+
+    fn stream_args(
+        &self,
+        format: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        let archive_type = match format {
+            "tgz" | "gz" => "gzip",
+            "bz2" => "bzip2",
+            format => format,
+        };
+
+        let mut destination_arg = OsString::from("-o");
+        destination_arg.push(destination);
+
+        // -si - read the archive from stdin, -t - archive type, since there is no file name to go by.
+        vec![
+            "x".into(),
+            "-si".into(),
+            format!("-t{archive_type}").into(),
+            destination_arg,
+            password_arg(password),
+            "-y".into(),
+        ]
+    }
+
+    fn progress_args(&self) -> Vec<OsString> {
+        // -bsp1 - progress to stdout (https://documentation.help/7-Zip/bs.htm)
+        vec!["-bsp1".into()]
+    }
+
+    fn parse_progress(&self, output: &str) -> Option<u64> {
+        // eg. ` 45% 12 - App/app.exe`
+        let (percent, _) = output.trim_start().split_once('%')?;
+        percent.parse().ok().filter(|percent| *percent <= 100)
+    }
+
+    fn is_wrong_password(&self, errors: &str) -> bool {
+        // eg. `Cannot open encrypted archive. Wrong password?`
+        errors.contains("Wrong password")
+    }
+
+    fn map_exit_code(&self, code: i32) -> Result<(), ExtractError> {
+        // found here: https://documentation.help/7-Zip/exit_codes.htm
+        match code {
+            // ok (no error or warning)
+            0 | 1 => Ok(()),
+            2 => Err(ExtractError::Fatal(self.program())),
+            7 => Err(ExtractError::CommandLine(self.program())),
+            8 => Err(ExtractError::OutOfMemory(self.program())),
+            255 => Err(ExtractError::UserStopped(self.program())),
+            code => Err(ExtractError::UnknownExitCode(self.program(), code)),
+        }
+    }
+}
+
+/// The `-p` switch giving 7z `password`.
+///
+/// Without a password it is given an empty one, so it fails on encrypted archives instead of asking for one.
+fn password_arg(password: Option<&str>) -> OsString {
+    format!("-p{}", password.unwrap_or_default()).into()
+}
@@ -0,0 +1,58 @@
+use std::{ffi::OsString, path::Path};
+
+use super::{Answers, ExternalExtractor, ExtractError};
+use crate::volumes::VolumeKind;
+
+/// Extract with `unar` from [The Unarchiver](https://theunarchiver.com/command-line).
+pub struct Unar;
+
+impl ExternalExtractor for Unar {
+    fn program(&self) -> &'static str {
+        "unar"
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        &["rar", "zip", "7z", "tar", "tgz", "gz", "xz", "bz2"]
+    }
+
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        &[VolumeKind::RarParts, VolumeKind::RarLegacy]
+    }
+
+    fn args(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        // -D - do not create a containing directory, like every other extractor.
+        let mut args = vec!["-D".into(), "-o".into(), destination.into()];
+        if let Some(password) = password {
+            args.extend(["-p".into(), password.into()]);
+        }
+        match answers {
+            Answers::Ask => {}
+            // -f - overwrite existing files
+            Answers::Yes => args.push("-f".into()),
+            // -s - skip existing files
+            Answers::No => args.push("-s".into()),
+        }
+        args.push(archive.into());
+
+        args
+    }
+
+    fn is_wrong_password(&self, errors: &str) -> bool {
+        // eg. `This archive requires a password to unpack.`
+        errors.to_lowercase().contains("password")
+    }
+
+    fn map_exit_code(&self, code: i32) -> Result<(), ExtractError> {
+        match code {
+            0 => Ok(()),
+            1 => Err(ExtractError::Fatal(self.program())),
+            code => Err(ExtractError::UnknownExitCode(self.program(), code)),
+        }
+    }
+}
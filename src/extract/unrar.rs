@@ -0,0 +1,64 @@
+use std::{ffi::OsString, path::Path};
+
+use super::{Answers, ExternalExtractor, ExtractError};
+use crate::volumes::VolumeKind;
+
+/// Extract with `unrar` from [RARLAB](https://www.rarlab.com/).
+pub struct Unrar;
+
+impl ExternalExtractor for Unrar {
+    fn program(&self) -> &'static str {
+        "unrar"
+    }
+
+    fn formats(&self) -> &'static [&'static str] {
+        &["rar"]
+    }
+
+    fn volume_kinds(&self) -> &'static [VolumeKind] {
+        &[VolumeKind::RarParts, VolumeKind::RarLegacy]
+    }
+
+    fn args(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        answers: Answers,
+        password: Option<&str>,
+    ) -> Vec<OsString> {
+        // unrar treats the last argument as a destination only if it ends with a separator.
+        let mut destination_arg = OsString::from(destination);
+        destination_arg.push(std::path::MAIN_SEPARATOR_STR);
+
+        // x - extract with full paths, -p- - do not ask for a password
+        let password =
+            password.map_or_else(|| "-p-".to_string(), |password| format!("-p{password}"));
+        let mut args = vec!["x".into(), password.into()];
+        match answers {
+            Answers::Ask => {}
+            // -o+ - overwrite existing files
+            Answers::Yes => args.extend(["-y".into(), "-o+".into()]),
+            // -o- - do not overwrite existing files
+            Answers::No => args.push("-o-".into()),
+        }
+        args.extend([archive.into(), destination_arg]);
+
+        args
+    }
+
+    fn map_exit_code(&self, code: i32) -> Result<(), ExtractError> {
+        // found in unrar's errhnd.hpp
+        match code {
+            // ok (no error or warning)
+            0 | 1 => Ok(()),
+            2 => Err(ExtractError::Fatal(self.program())),
+            3 => Err(ExtractError::Corrupt(self.program())),
+            5 | 9 => Err(ExtractError::Write(self.program())),
+            7 => Err(ExtractError::CommandLine(self.program())),
+            8 => Err(ExtractError::OutOfMemory(self.program())),
+            11 => Err(ExtractError::WrongPassword(self.program())),
+            255 => Err(ExtractError::UserStopped(self.program())),
+            code => Err(ExtractError::UnknownExitCode(self.program(), code)),
+        }
+    }
+}
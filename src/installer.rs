@@ -0,0 +1,855 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use glob::{glob, Pattern};
+
+use crate::{
+    checksum::{Checksums, CHECKSUM_EXTS},
+    extract::{self, ExtractorKind},
+    interact::{AssumeNo, AssumeYes, Interactor, Terminal},
+    manifest::{self, record_files, FileRecord, Flags, Manifest},
+    profile::{Profile, ShortcutProfile},
+    steps::{
+        check_sequence, clean_up, combined_name, create_destination, create_shortcut, extract,
+        extract_parts, find_final_extension, find_final_name, find_shortcut_executable,
+        flatten_dir, flatten_into, parse_app_name, run_hooks,
+    },
+    volumes::{VolumeKind, VolumeSet},
+    Error, PathExt,
+};
+
+/// How prompts are answered during an install, see [`Installer::interaction`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interaction {
+    /// Ask the user on the terminal.
+    #[default]
+    Prompt,
+    /// Assume the answer that continues the install, without asking.
+    AssumeYes,
+    /// Assume the answer that stops the install, without asking.
+    AssumeNo,
+}
+
+impl Interaction {
+    /// The [`Interactor`] answering prompts for this policy.
+    #[must_use]
+    pub fn interactor(self) -> Box<dyn Interactor> {
+        match self {
+            Self::Prompt => Box::new(Terminal),
+            Self::AssumeYes => Box::new(AssumeYes),
+            Self::AssumeNo => Box::new(AssumeNo),
+        }
+    }
+}
+
+/// What is removed after a successful install, see [`Installer::cleanup`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Cleanup {
+    /// Keep everything.
+    #[default]
+    None,
+    /// Remove the archive combined from the parts.
+    Combined,
+    /// Remove the parts, keeping the combined archive.
+    Parts,
+    /// Remove the combined archive and the parts.
+    All,
+}
+
+impl Cleanup {
+    /// The files to remove out of the `parts` an install used and the `archive` it extracted.
+    ///
+    /// Only an archive that is not one of the parts was combined.
+    fn files(self, parts: &[PathBuf], archive: Option<&Path>) -> Vec<PathBuf> {
+        let combined = archive
+            .filter(|archive| !parts.iter().any(|part| part == archive))
+            .map(Path::to_path_buf);
+
+        match self {
+            Self::None => Vec::new(),
+            Self::Combined => combined.into_iter().collect(),
+            Self::Parts => parts.to_vec(),
+            Self::All => parts.iter().cloned().chain(combined).collect(),
+        }
+    }
+}
+
+/// How inner directories of the install folder are flattened, see [`Installer::flatten`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Flatten {
+    /// Leave the extracted files as they are.
+    Off,
+    /// Flatten an inner directory named after the app.
+    #[default]
+    Keyword,
+    /// Flatten the only entry of the install folder for as long as it is a directory, whatever its name.
+    Repeated,
+}
+
+/// Installs an app packaged in compressed parts.
+///
+/// ```no_run
+/// use partsinstall::{Installer, Interaction};
+///
+/// let report = Installer::new("App.7z.001", "C:/Apps")
+///     .working_dir("C:/Downloads")
+///     .shortcut(false)
+///     .interaction(Interaction::AssumeYes)
+///     .run()?;
+///
+/// println!("Installed to {:?}", report.destination);
+/// # Ok::<(), partsinstall::Error>(())
+/// ```
+pub struct Installer {
+    name: PathBuf,
+    destination: PathBuf,
+    working_dir: PathBuf,
+    flatten: Option<Flatten>,
+    shortcut: bool,
+    stream: bool,
+    extractor: Option<ExtractorKind>,
+    password: Option<String>,
+    interactor: Box<dyn Interactor>,
+    manifest_dir: Option<PathBuf>,
+    cleanup: Cleanup,
+    profile: Option<PathBuf>,
+}
+
+/// What an install did, returned by [`Installer::run`].
+#[derive(Clone, Debug)]
+pub struct InstallReport {
+    /// The app name parsed from the name given.
+    pub app_name: String,
+    /// The parts the app was installed from.
+    pub parts: Vec<PathBuf>,
+    /// The archive given to the extractor, `None` if the parts were streamed.
+    pub archive: Option<PathBuf>,
+    /// The folder the app was installed to.
+    pub destination: PathBuf,
+    /// The shortcut created, if any.
+    pub shortcut: Option<PathBuf>,
+    /// The manifest written, if any.
+    pub manifest: Option<PathBuf>,
+    /// The files removed after the install, see [`Installer::cleanup`].
+    pub cleaned: Vec<PathBuf>,
+    /// The total size of the files in `cleaned`.
+    pub reclaimed: u64,
+    pub combine_time: Duration,
+    pub extract_time: Duration,
+    pub flatten_time: Duration,
+}
+
+/// What an install would do, returned by [`Installer::plan`].
+#[derive(Clone, Debug)]
+pub struct InstallPlan {
+    /// The app name parsed from the name given.
+    pub app_name: String,
+    /// The parts found, in the order they would be combined or extracted.
+    pub parts: Vec<PathBuf>,
+    pub volume_kind: VolumeKind,
+    /// The checksum files the parts would be verified with.
+    pub checksum_files: Vec<PathBuf>,
+    /// The archive that would be given to the extractor, `None` if the parts would be streamed.
+    pub archive: Option<PathBuf>,
+    /// Whether the parts would be combined into `archive`.
+    pub combine: bool,
+    /// The folder the app would be installed to.
+    pub destination: PathBuf,
+    /// The name of the extractor that would be used.
+    pub extractor: &'static str,
+    /// The profile that would be applied, if any.
+    pub profile: Option<PathBuf>,
+    /// How inner directories would be flattened.
+    pub flatten: Flatten,
+    /// Whether a shortcut would be created.
+    pub shortcut: bool,
+    /// The commands that would be run once the app is installed.
+    pub post_install: Vec<String>,
+    pub contents: ArchiveContents,
+}
+
+/// What extracting the archive would write, as far as [`Installer::plan`] can tell without extracting it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveContents {
+    /// The archive's index was read.
+    Listed {
+        /// The number of files and folders in the archive.
+        entries: usize,
+        /// The inner directory whose contents would be moved into the install folder,
+        /// `None` if there is none or flattening is off.
+        inner_dir: Option<PathBuf>,
+        /// The executable a shortcut would be made to, `None` if none was found or shortcuts are off.
+        executable: Option<PathBuf>,
+    },
+    /// The archive could not be listed, for the reason given.
+    Unknown(String),
+}
+
+impl Installer {
+    /// Install the app called `name` into a folder in `destination`.
+    #[must_use]
+    pub fn new(name: impl Into<PathBuf>, destination: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            destination: destination.into(),
+            working_dir: PathBuf::from("."),
+            flatten: None,
+            shortcut: true,
+            stream: false,
+            extractor: None,
+            password: None,
+            interactor: Interaction::default().interactor(),
+            manifest_dir: manifest::state_dir(),
+            cleanup: Cleanup::None,
+            profile: None,
+        }
+    }
+
+    /// Set the name of the app in the working directory to install.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<PathBuf>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the folder the app is installed into.
+    #[must_use]
+    pub fn destination(mut self, destination: impl Into<PathBuf>) -> Self {
+        self.destination = destination.into();
+        self
+    }
+
+    /// Set the directory parts are looked for and combined in. Defaults to the current directory.
+    #[must_use]
+    pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = working_dir.into();
+        self
+    }
+
+    /// Set how inner directories are flattened, over the app's profile.
+    /// Defaults to the profile's strategy, or [`Flatten::Keyword`].
+    #[must_use]
+    pub fn flatten(mut self, flatten: Flatten) -> Self {
+        self.flatten = Some(flatten);
+        self
+    }
+
+    /// Set whether a shortcut is created. Defaults to `true`.
+    #[must_use]
+    pub fn shortcut(mut self, shortcut: bool) -> Self {
+        self.shortcut = shortcut;
+        self
+    }
+
+    /// Set whether split parts are streamed into the extractor instead of combined on disk. Defaults to `false`.
+    #[must_use]
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Set the extractor to use. Defaults to the first installed one that supports the archive.
+    #[must_use]
+    pub fn extractor(mut self, extractor: Option<ExtractorKind>) -> Self {
+        self.extractor = extractor;
+        self
+    }
+
+    /// Set the password encrypted archives are decrypted with.
+    /// Defaults to `None`, asking for one if the archive turns out to be encrypted.
+    #[must_use]
+    pub fn password(mut self, password: Option<String>) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Set how prompts are answered. Defaults to [`Interaction::Prompt`].
+    #[must_use]
+    pub fn interaction(mut self, interaction: Interaction) -> Self {
+        self.interactor = interaction.interactor();
+        self
+    }
+
+    /// Set what answers prompts, for answers that are not covered by [`Interaction`].
+    #[must_use]
+    pub fn interactor(mut self, interactor: impl Interactor + 'static) -> Self {
+        self.interactor = Box::new(interactor);
+        self
+    }
+
+    /// Set the directory the install's [`Manifest`] is written to, `None` to not write one.
+    /// Defaults to [`manifest::state_dir`].
+    #[must_use]
+    pub fn manifest_dir(mut self, manifest_dir: Option<PathBuf>) -> Self {
+        self.manifest_dir = manifest_dir;
+        self
+    }
+
+    /// Set what is removed once the app is extracted and flattened. Defaults to [`Cleanup::None`].
+    #[must_use]
+    pub fn cleanup(mut self, cleanup: Cleanup) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+
+    /// Set the [`Profile`] to install with. Defaults to `None`,
+    /// using the [`PROFILE_NAME`](crate::profile::PROFILE_NAME) file next to the parts if there is one.
+    ///
+    /// The options set on the installer take precedence over the profile.
+    /// Post-install commands of a profile set here are run without asking,
+    /// those of a profile found next to the parts only when the user confirms them.
+    #[must_use]
+    pub fn profile(mut self, profile: Option<PathBuf>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Run the install.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if any step of the install fails, or the user cancels.
+    pub fn run(&self) -> Result<InstallReport, Error> {
+        self.check_dirs()?;
+
+        let name = self.working_dir.join(&self.name);
+        let app_name = parse_app_name(&name).ok_or_else(|| Error::AppName(self.name.clone()))?;
+        println!("Parsed name as: {app_name}\n");
+
+        let (parts, checksum_files) = self.find_parts(&name, &app_name)?;
+
+        let settings = self.settings(&name, &app_name)?;
+        if let Some(profile) = &settings.profile {
+            println!("Using profile {profile:?}\n");
+        }
+
+        let checksums = Checksums::load(&checksum_files, &parts)?;
+        if !checksums.is_empty() {
+            println!(
+                "Verifying parts with checksums from {:?}\n",
+                checksums.files()
+            );
+        }
+
+        let mut volume_set = VolumeSet::detect(parts);
+        let volume_kind = volume_set.kind;
+
+        let destination = settings.destination.clone();
+
+        // everything is installed into a staging folder first, which is removed if the install fails.
+        let (staging, archive, combine_time, extract_time) =
+            if self.stream && volume_kind == VolumeKind::Split {
+                println!("Extracting {app_name} to {destination:?}");
+
+                // an archive combined by an earlier run is found with the parts, but is not one of them.
+                let final_name = combined_name(&app_name, &volume_set.volumes, &self.working_dir)?;
+                volume_set
+                    .volumes
+                    .retain(|file| file.file_name() != final_name.file_name());
+                check_sequence(&mut volume_set.volumes)?;
+                checksums.verify_all(&volume_set.volumes)?;
+                let staging = create_destination(&destination, self.interactor.as_ref())?;
+
+                // combining happens while extracting, so it is counted as extract time.
+                let extract_start = Instant::now();
+                extract_parts(
+                    &mut volume_set.volumes,
+                    staging.dir(),
+                    settings.extractor,
+                    settings.password.as_deref(),
+                )?;
+                (staging, None, Duration::ZERO, extract_start.elapsed())
+            } else {
+                let (archive, combine_time) = find_final_name(
+                    &app_name,
+                    &mut volume_set,
+                    &self.working_dir,
+                    &checksums,
+                    self.interactor.as_ref(),
+                )?;
+
+                println!("\nExtracting {app_name} to {destination:?}");
+
+                let staging = create_destination(&destination, self.interactor.as_ref())?;
+
+                let extract_start = Instant::now();
+                extract(
+                    &archive,
+                    staging.dir(),
+                    settings.extractor,
+                    volume_kind,
+                    settings.password.as_deref(),
+                    self.interactor.as_ref(),
+                )?;
+                (
+                    staging,
+                    Some(archive),
+                    combine_time,
+                    extract_start.elapsed(),
+                )
+            };
+
+        let flatten_start = Instant::now();
+        match &settings.flatten_dir {
+            Some(inner_dir) if settings.flatten != Flatten::Off => {
+                flatten_into(&staging.dir().join(inner_dir), staging.dir())?;
+            }
+            _ => flatten_dir(&app_name, staging.dir(), settings.flatten)?,
+        }
+        let flatten_time = flatten_start.elapsed();
+
+        // found while the files are staged, so a missing executable fails before anything is in place.
+        let executable = match &settings.shortcut {
+            Some(profile) => {
+                println!("Finding the executable for the start menu shortcut:");
+                find_shortcut_executable(
+                    &app_name,
+                    staging.dir(),
+                    profile,
+                    self.interactor.as_ref(),
+                )?
+            }
+            None => None,
+        };
+
+        // only what the install wrote is recorded, not what was already in the destination.
+        let files = self
+            .manifest_dir
+            .as_ref()
+            .map(|_| record_files(staging.dir()))
+            .transpose()?;
+
+        staging.commit()?;
+
+        let mut report = InstallReport {
+            app_name: app_name.into_owned(),
+            // an archive combined by an earlier run is left out of the parts when combining.
+            parts: volume_set.volumes,
+            archive,
+            destination,
+            shortcut: None,
+            manifest: None,
+            cleaned: Vec::new(),
+            reclaimed: 0,
+            combine_time,
+            extract_time,
+            flatten_time,
+        };
+
+        // written as soon as the app is in place, so it can be uninstalled even if a later step fails.
+        let files = files.unwrap_or_default();
+        report.manifest = self.save_manifest(&report, &files, &settings)?;
+
+        match (&settings.shortcut, executable) {
+            (Some(profile), Some(executable)) => {
+                println!("Creating start menu shortcut:");
+                let shortcut =
+                    create_shortcut(&report.app_name, &report.destination, &executable, profile)?;
+                report.shortcut = Some(shortcut);
+                self.save_manifest(&report, &files, &settings)?;
+            }
+            (Some(_), None) => println!("Not creating start menu shortcut, no executable."),
+            (None, _) => println!("Not creating start menu shortcut."),
+        }
+
+        if !settings.post_install.is_empty() {
+            // a profile given explicitly was chosen by the user, unlike one found next to the parts.
+            let trusted = self.profile.is_some();
+            run_hooks(
+                &settings.post_install,
+                &report.destination,
+                trusted,
+                self.interactor.as_ref(),
+            )?;
+        }
+
+        // the parts are only removed once nothing can fail anymore, so the install can always be run again.
+        let files = self.cleanup.files(&report.parts, report.archive.as_deref());
+        (report.cleaned, report.reclaimed) = clean_up(&files);
+
+        Ok(report)
+    }
+
+    /// Work out what [`Installer::run`] would do, without combining, extracting, or writing anything.
+    ///
+    /// The archive is listed to find what would be flattened and made a shortcut to,
+    /// reading the parts as one file where they would be combined.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the parts could not be found or are not numbered in sequence,
+    /// or no extractor supports the archive.
+    pub fn plan(&self) -> Result<InstallPlan, Error> {
+        self.check_dirs()?;
+
+        let name = self.working_dir.join(&self.name);
+        let app_name = parse_app_name(&name).ok_or_else(|| Error::AppName(self.name.clone()))?;
+
+        let (parts, checksum_files) = self.find_parts(&name, &app_name)?;
+        let settings = self.settings(&name, &app_name)?;
+
+        let mut volume_set = VolumeSet::detect(parts);
+        let volume_kind = volume_set.kind;
+
+        // the same choices as `run` and `find_final_name`.
+        let (archive, combine) = if self.stream && volume_kind == VolumeKind::Split {
+            let final_name = combined_name(&app_name, &volume_set.volumes, &self.working_dir)?;
+            volume_set
+                .volumes
+                .retain(|file| file.file_name() != final_name.file_name());
+            check_sequence(&mut volume_set.volumes)?;
+            (None, false)
+        } else if volume_kind.is_multi_volume() || volume_set.volumes.len() == 1 {
+            (Some(volume_set.volumes[0].clone()), false)
+        } else {
+            let final_name = combined_name(&app_name, &volume_set.volumes, &self.working_dir)?;
+            volume_set
+                .volumes
+                .retain(|file| file.file_name() != final_name.file_name());
+            check_sequence(&mut volume_set.volumes)?;
+            (Some(final_name), true)
+        };
+
+        let format = match &archive {
+            Some(archive) => archive
+                .lossy_extension()
+                .map(|ext| ext.to_lowercase())
+                .unwrap_or_default(),
+            None => find_final_extension(&volume_set.volumes)?.to_lowercase(),
+        };
+
+        let extractor = match (settings.extractor, &archive) {
+            (Some(kind), _) => kind.extractor(),
+            (None, Some(archive)) => extract::probe(archive, volume_kind)
+                .ok_or_else(|| Error::NoExtractor(archive.clone()))?,
+            (None, None) => extract::probe_stream(&format)
+                .ok_or_else(|| Error::NoStreamExtractor(format.clone()))?,
+        };
+
+        let contents = Self::contents(&app_name, &volume_set, &format, &settings);
+
+        Ok(InstallPlan {
+            app_name: app_name.into_owned(),
+            parts: volume_set.volumes,
+            volume_kind,
+            checksum_files,
+            archive,
+            combine,
+            destination: settings.destination,
+            extractor: extractor.name(),
+            profile: settings.profile,
+            flatten: settings.flatten,
+            shortcut: settings.shortcut.is_some(),
+            post_install: settings.post_install,
+            contents,
+        })
+    }
+
+    /// Apply the [`Profile`] of the app `app_name` installed from `name` over the installer's options.
+    fn settings(&self, name: &Path, app_name: &str) -> Result<Settings, Error> {
+        let path = self
+            .profile
+            .clone()
+            .or_else(|| Profile::find(self.parts_dir(name)));
+        let profile = path
+            .as_deref()
+            .map(Profile::load)
+            .transpose()?
+            .unwrap_or_default();
+
+        // a profile found next to the parts could read any file or environment variable as the password,
+        // which external extractors are passed on their command line.
+        let password = match &self.password {
+            Some(password) => Some(password.clone()),
+            None if self.profile.is_some() => profile.password()?,
+            None => {
+                if let Some(path) = path.as_ref().filter(|_| profile.has_password()) {
+                    println!(
+                        "Not reading the password named in {path:?}, only a profile given with --profile is trusted with one.\n"
+                    );
+                }
+                None
+            }
+        };
+
+        Ok(Settings {
+            destination: self
+                .destination
+                .join(profile.folder.as_deref().unwrap_or(app_name)),
+            flatten: self.flatten.or(profile.flatten).unwrap_or_default(),
+            flatten_dir: profile.flatten_dir,
+            shortcut: (self.shortcut && profile.shortcut.create.unwrap_or(true))
+                .then_some(profile.shortcut),
+            extractor: self.extractor.or(profile.extractor),
+            password,
+            post_install: profile.post_install,
+            profile: path,
+        })
+    }
+
+    /// Check the destination and working directory exist.
+    fn check_dirs(&self) -> Result<(), Error> {
+        if !self.destination.exists() {
+            return Err(Error::MissingDestination(self.destination.clone()));
+        }
+
+        if !self.working_dir.is_dir() {
+            return Err(Error::WorkingDir(
+                self.working_dir.clone(),
+                std::io::ErrorKind::NotFound.into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// List the `format` archive `volume_set` makes up with the built-in extractor,
+    /// to find what installing it with `settings` would flatten and make a shortcut to.
+    #[cfg(feature = "native")]
+    fn contents(
+        app_name: &str,
+        volume_set: &VolumeSet,
+        format: &str,
+        settings: &Settings,
+    ) -> ArchiveContents {
+        use std::{collections::BTreeSet, fs::File, io::BufReader, path::Component};
+
+        use crate::{
+            extract::{ArchiveEntry, ExtractError, Native},
+            parts::PartsReader,
+            platform,
+            steps::{find_app_executable, is_inner_dir},
+        };
+
+        if volume_set.kind.is_multi_volume() {
+            return ArchiveContents::Unknown(format!(
+                "a {} archive can only be listed by extracting it",
+                volume_set.kind
+            ));
+        }
+
+        let destination = &settings.destination;
+        let password = settings.password.as_deref();
+        let listed = if volume_set.kind == VolumeKind::Single {
+            File::open(&volume_set.volumes[0])
+                .map_err(ExtractError::Io)
+                .and_then(|file| Native.list(BufReader::new(file), format, password))
+        } else {
+            PartsReader::open(&volume_set.volumes)
+                .map_err(ExtractError::Io)
+                .and_then(|reader| Native.list(BufReader::new(reader), format, password))
+        };
+
+        let entries = match listed {
+            Ok(Some(entries)) => entries,
+            Ok(None) => {
+                return ArchiveContents::Unknown(format!(
+                    "a .{format} archive can only be listed by extracting it"
+                ))
+            }
+            Err(err) => return ArchiveContents::Unknown(err.to_string()),
+        };
+
+        // tar entries can start with `./`.
+        let entries: Vec<(PathBuf, &ArchiveEntry)> = entries
+            .iter()
+            .map(|entry| {
+                let path = entry
+                    .path
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .collect();
+                (path, entry)
+            })
+            .collect();
+
+        // whether `dir` is a directory in the archive, listed itself or only by its contents.
+        let is_dir = |dir: &Path| {
+            entries
+                .iter()
+                .any(|(path, entry)| path.starts_with(dir) && (entry.is_dir || path != dir))
+        };
+
+        let inner_dir = match (settings.flatten, &settings.flatten_dir) {
+            (Flatten::Off, _) => None,
+            (_, Some(inner_dir)) => Some(inner_dir.clone()),
+            (Flatten::Keyword, None) => entries.iter().find_map(|(path, _)| {
+                let top = Path::new(path.components().next()?.as_os_str());
+                (is_dir(top) && is_inner_dir(app_name, top)).then(|| top.to_path_buf())
+            }),
+            (Flatten::Repeated, None) => {
+                let mut inner_dir = PathBuf::new();
+                loop {
+                    let children: BTreeSet<&Path> = entries
+                        .iter()
+                        .filter_map(|(path, _)| {
+                            let child = path.strip_prefix(&inner_dir).ok()?.components().next()?;
+                            Some(Path::new(child.as_os_str()))
+                        })
+                        .collect();
+
+                    let mut children = children.into_iter();
+                    let (Some(child), None) = (children.next(), children.next()) else {
+                        break;
+                    };
+
+                    let child = inner_dir.join(child);
+                    if !is_dir(&child) {
+                        break;
+                    }
+                    inner_dir = child;
+                }
+                (inner_dir != Path::new("")).then_some(inner_dir)
+            }
+        };
+
+        // what ends up directly in `destination` once the inner directory is flattened.
+        let top_level = inner_dir.as_deref().unwrap_or(Path::new(""));
+        let mut executables: Vec<PathBuf> = entries
+            .iter()
+            .filter(|(path, entry)| {
+                !entry.is_dir
+                    && path.parent() == Some(top_level)
+                    && platform::is_executable_entry(path, entry.mode)
+            })
+            .filter_map(|(path, _)| Some(destination.join(path.file_name()?)))
+            .collect();
+        executables.sort();
+
+        let executable =
+            settings
+                .shortcut
+                .as_ref()
+                .and_then(|shortcut| match &shortcut.executable {
+                    Some(executable) => Some(destination.join(executable)),
+                    None => find_app_executable(app_name, &executables)
+                        .or(executables.first())
+                        .cloned(),
+                });
+
+        ArchiveContents::Listed {
+            entries: entries.len(),
+            inner_dir: inner_dir.map(|inner_dir| destination.join(inner_dir)),
+            executable,
+        }
+    }
+
+    #[cfg(not(feature = "native"))]
+    fn contents(
+        _app_name: &str,
+        _volume_set: &VolumeSet,
+        _format: &str,
+        _settings: &Settings,
+    ) -> ArchiveContents {
+        ArchiveContents::Unknown("listing archives needs the built-in extractor".to_string())
+    }
+
+    /// Write the [`Manifest`] of `report`'s install, which wrote `files`, if manifests are kept.
+    fn save_manifest(
+        &self,
+        report: &InstallReport,
+        files: &[FileRecord],
+        settings: &Settings,
+    ) -> Result<Option<PathBuf>, Error> {
+        let Some(manifest_dir) = &self.manifest_dir else {
+            return Ok(None);
+        };
+
+        Manifest::new(report, files.to_vec(), self.flags(settings))?
+            .save(manifest_dir)
+            .map(Some)
+    }
+
+    /// The options recorded in the install's [`Manifest`], once its profile was applied as `settings`.
+    fn flags(&self, settings: &Settings) -> Flags {
+        Flags {
+            flatten: settings.flatten != Flatten::Off,
+            flatten_strategy: settings
+                .flatten
+                .to_possible_value()
+                .map(|value| value.get_name().to_string()),
+            shortcut: settings.shortcut.is_some(),
+            stream: self.stream,
+            extractor: settings
+                .extractor
+                .and_then(|kind| kind.to_possible_value())
+                .map(|value| value.get_name().to_string()),
+            profile: settings.profile.clone(),
+        }
+    }
+
+    /// The directory the parts of `name` are looked for in, `name` itself if it is a directory.
+    fn parts_dir<'a>(&'a self, name: &'a Path) -> &'a Path {
+        if name.is_dir() {
+            name
+        } else {
+            &self.working_dir
+        }
+    }
+
+    /// Find the files starting with `app_name`, inside `name` if it is a directory,
+    /// and the checksum files named after the archive next to them.
+    fn find_parts(
+        &self,
+        name: &Path,
+        app_name: &str,
+    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
+        let dir = self.parts_dir(name);
+
+        // escape the directory so only the app name is matched on.
+        let dir = PathBuf::from(Pattern::escape(&dir.to_string_lossy()));
+        let glob_pattern = dir.join(format!("{}*", Pattern::escape(app_name)));
+
+        let (checksum_files, files): (Vec<PathBuf>, Vec<PathBuf>) =
+            glob(&glob_pattern.to_string_lossy())?
+                .filter_map(Result::ok)
+                .partition(|file| is_checksum_file(file));
+
+        if files.is_empty() {
+            return Err(Error::NoFilesFound(app_name.to_string()));
+        }
+
+        // only checksum files for this archive, eg. `App.sfv` or `App.7z.001.sha256`, not `AppOther.sfv`.
+        let app_name = app_name.to_lowercase();
+        let checksum_files = checksum_files
+            .into_iter()
+            .filter(|file| {
+                file.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_lowercase())
+                    .is_some_and(|stem| {
+                        stem == app_name
+                            || stem
+                                .strip_prefix(&app_name)
+                                .is_some_and(|rest| rest.starts_with('.'))
+                    })
+            })
+            .collect();
+
+        Ok((files, checksum_files))
+    }
+}
+
+/// The options of one install, once its [`Profile`] is applied over the [`Installer`]'s.
+struct Settings {
+    /// The path of the profile applied, if any.
+    profile: Option<PathBuf>,
+    /// The folder the app is installed to.
+    destination: PathBuf,
+    flatten: Flatten,
+    /// The inner directory to flatten, instead of the one named after the app.
+    flatten_dir: Option<PathBuf>,
+    /// How the shortcut is made, `None` if no shortcut is created.
+    shortcut: Option<ShortcutProfile>,
+    extractor: Option<ExtractorKind>,
+    password: Option<String>,
+    post_install: Vec<String>,
+}
+
+/// Returns true if `path` is a checksum file, which is never a part.
+fn is_checksum_file(path: &Path) -> bool {
+    path.lossy_extension()
+        .is_some_and(|ext| CHECKSUM_EXTS.contains(&ext.to_lowercase().as_ref()))
+}
@@ -0,0 +1,258 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+};
+
+use crate::{platform, print_flush, prompt, prompt_password, Error};
+
+/// Answers the questions asked during an install.
+pub trait Interactor {
+    /// Ask a yes/no `question`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an answer could not be read.
+    fn confirm(&self, question: &str) -> Result<bool, Error>;
+
+    /// Ask a yes/no `question` whose yes could do harm, eg. running commands that came with the parts.
+    /// Unlike [`Interactor::confirm`], yes is never assumed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an answer could not be read.
+    fn confirm_untrusted(&self, question: &str) -> Result<bool, Error> {
+        self.confirm(question)
+    }
+
+    /// Ask the user to choose one of `options`, returning its index, or `None` to choose none.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an answer could not be read.
+    fn choose(&self, question: &str, options: &[String]) -> Result<Option<usize>, Error>;
+
+    /// Ask for an existing path inside `start`, returning `None` to skip.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an answer could not be read.
+    fn ask_path(&self, question: &str, start: &Path) -> Result<Option<PathBuf>, Error>;
+
+    /// Ask for a password without showing it, returning `None` to give up.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an answer could not be read.
+    fn ask_password(&self, question: &str) -> Result<Option<String>, Error>;
+
+    /// Returns true if a user answers, in which case extractors are allowed to prompt too.
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    /// Returns true if every question is answered yes without asking, in which case extractors answer yes too.
+    fn assumes_yes(&self) -> bool {
+        false
+    }
+}
+
+/// Ask the user on the terminal, retrying until a valid answer is given.
+pub struct Terminal;
+
+impl Interactor for Terminal {
+    fn confirm(&self, question: &str) -> Result<bool, Error> {
+        print_flush!("{question} (y/n): ");
+
+        Ok(parse_confirm(&prompt()?))
+    }
+
+    fn choose(&self, question: &str, options: &[String]) -> Result<Option<usize>, Error> {
+        println!("\n{question}");
+        for (n, option) in options.iter().enumerate() {
+            println!("{}: {option}", n + 1);
+        }
+
+        loop {
+            print_flush!("Choice: ");
+
+            if let Some(choice) = parse_choice(&prompt()?, options.len()) {
+                return Ok(Some(choice));
+            }
+        }
+    }
+
+    fn ask_path(&self, question: &str, start: &Path) -> Result<Option<PathBuf>, Error> {
+        print_flush!("{question} (s)kip or (g)ive path manually? ");
+
+        if !prompt()?.eq_ignore_ascii_case("g") {
+            return Ok(None);
+        }
+
+        loop {
+            print_flush!("Path: {}{MAIN_SEPARATOR}", platform::display_path(start));
+
+            if let Some(path) = parse_path(&prompt()?, start) {
+                return Ok(Some(path));
+            }
+        }
+    }
+
+    fn ask_password(&self, question: &str) -> Result<Option<String>, Error> {
+        print_flush!("{question} (leave empty to give up): ");
+
+        Ok(parse_password(prompt_password()?))
+    }
+
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Answer yes to every question without asking, like the `-y` flag.
+///
+/// Choices and paths are skipped.
+pub struct AssumeYes;
+
+impl Interactor for AssumeYes {
+    fn confirm(&self, question: &str) -> Result<bool, Error> {
+        println!("{question} (y/n): y");
+        Ok(true)
+    }
+
+    fn confirm_untrusted(&self, question: &str) -> Result<bool, Error> {
+        println!("{question} (y/n): n, not assumed");
+        Ok(false)
+    }
+
+    fn choose(&self, _question: &str, _options: &[String]) -> Result<Option<usize>, Error> {
+        Ok(None)
+    }
+
+    fn ask_path(&self, _question: &str, _start: &Path) -> Result<Option<PathBuf>, Error> {
+        Ok(None)
+    }
+
+    fn ask_password(&self, _question: &str) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn assumes_yes(&self) -> bool {
+        true
+    }
+}
+
+/// Answer no to every question without asking, so an install stops at the first prompt.
+pub struct AssumeNo;
+
+impl Interactor for AssumeNo {
+    fn confirm(&self, question: &str) -> Result<bool, Error> {
+        println!("{question} (y/n): n");
+        Ok(false)
+    }
+
+    fn choose(&self, _question: &str, _options: &[String]) -> Result<Option<usize>, Error> {
+        Ok(None)
+    }
+
+    fn ask_path(&self, _question: &str, _start: &Path) -> Result<Option<PathBuf>, Error> {
+        Ok(None)
+    }
+
+    fn ask_password(&self, _question: &str) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+}
+
+/// Answer from a list of answers, as they would be typed on the terminal. Useful for tests.
+///
+/// Running out of answers, or an invalid answer, is an error instead of a retry.
+pub struct Scripted {
+    answers: RefCell<VecDeque<String>>,
+}
+
+impl Scripted {
+    #[must_use]
+    pub fn new<S: Into<String>>(answers: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            answers: RefCell::new(answers.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    /// Returns true if every answer was used.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.answers.borrow().is_empty()
+    }
+
+    fn next(&self, question: &str) -> Result<String, Error> {
+        self.answers.borrow_mut().pop_front().ok_or_else(|| {
+            Error::Prompt(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("No scripted answer left for {question:?}"),
+            ))
+        })
+    }
+}
+
+impl Interactor for Scripted {
+    fn confirm(&self, question: &str) -> Result<bool, Error> {
+        Ok(parse_confirm(&self.next(question)?))
+    }
+
+    fn choose(&self, question: &str, options: &[String]) -> Result<Option<usize>, Error> {
+        let answer = self.next(question)?;
+
+        parse_choice(&answer, options.len())
+            .map(Some)
+            .ok_or_else(|| invalid_answer(&answer, question))
+    }
+
+    fn ask_path(&self, question: &str, start: &Path) -> Result<Option<PathBuf>, Error> {
+        let answer = self.next(question)?;
+        if !answer.eq_ignore_ascii_case("g") {
+            return Ok(None);
+        }
+
+        let path = self.next(question)?;
+        parse_path(&path, start)
+            .map(Some)
+            .ok_or_else(|| invalid_answer(&path, question))
+    }
+
+    fn ask_password(&self, question: &str) -> Result<Option<String>, Error> {
+        Ok(parse_password(self.next(question)?))
+    }
+}
+
+fn invalid_answer(answer: &str, question: &str) -> Error {
+    Error::Prompt(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{answer:?} is not a valid answer to {question:?}"),
+    ))
+}
+
+/// Only `y` means yes.
+fn parse_confirm(answer: &str) -> bool {
+    answer.eq_ignore_ascii_case("y")
+}
+
+/// Parse a choice from 1 to `len` into an index.
+fn parse_choice(answer: &str, len: usize) -> Option<usize> {
+    answer
+        .parse::<usize>()
+        .ok()
+        .filter(|choice| (1..=len).contains(choice))
+        .map(|choice| choice - 1)
+}
+
+/// An empty password means giving up.
+fn parse_password(answer: String) -> Option<String> {
+    (!answer.is_empty()).then_some(answer)
+}
+
+/// Resolve `answer` inside `start`, if it exists.
+fn parse_path(answer: &str, start: &Path) -> Option<PathBuf> {
+    dunce::canonicalize(start.join(answer)).ok()
+}
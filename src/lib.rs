@@ -1,10 +1,46 @@
 #[cfg(test)]
 mod tests;
 
+mod error;
+pub use error::Error;
+
+mod installer;
+pub use installer::{
+    ArchiveContents, Cleanup, Flatten, InstallPlan, InstallReport, Installer, Interaction,
+};
+
+/// Verifying parts against the checksum files released with them.
+pub mod checksum;
+/// Defaults for command line options read from the user's config file.
+pub mod config;
+/// Backends that can extract archives.
+pub mod extract;
+/// Answering the questions asked during an install.
+pub mod interact;
+/// Listing installed apps.
+pub mod list;
+/// Records of what installs wrote.
+pub mod manifest;
+/// Reading multi-part archives as if they were one file.
+pub mod parts;
+/// Platform specific functionality.
+pub mod platform;
+/// Per-app install settings read from a profile file.
+pub mod profile;
+/// Showing the progress of long running steps.
+pub mod progress;
+/// Steps an install takes.
+pub mod steps;
+/// Removing installed apps.
+pub mod uninstall;
+/// Detecting how an archive was split into multiple files.
+pub mod volumes;
+
 use std::{
     borrow::Cow,
     cmp::Ordering,
-    io::stdin,
+    fs,
+    io::{self, stdin},
     path::{Path, PathBuf},
 };
 
@@ -27,7 +63,7 @@ macro_rules! print_flush {
 /// I chose these values based on the most commonly used archive types.
 ///
 /// <https://documentation.help/7-Zip/formats.htm>
-const ARCHIVE_EXTS: &[&str] = &["7z", "zip", "rar", "tgz"];
+const ARCHIVE_EXTS: &[&str] = &["7z", "zip", "rar", "tar", "tgz"];
 
 /// Provide convenience extension methods for [`Path`]
 pub trait PathExt {
@@ -78,6 +114,15 @@ pub fn name_has_keywords<'a>(keywords: impl IntoIterator<Item = &'a str>, path:
     keywords.into_iter().any(|kw| name.contains(kw))
 }
 
+/// Get the first numeric extension of `path` (file.7z.001 -> 1)
+#[must_use]
+pub fn numeric_extension(path: &Path) -> Option<u32> {
+    path.extension()?
+        .to_string_lossy()
+        .split('.')
+        .find_map(|ext| ext.parse().ok())
+}
+
 /// Compares numeric extensions of 2 paths (file.7z.001 < file.7z.002)
 ///
 /// # Panics
@@ -86,66 +131,82 @@ pub fn name_has_keywords<'a>(keywords: impl IntoIterator<Item = &'a str>, path:
 /// do not contain valid unicode, or do not contain a numeric extension
 #[must_use]
 pub fn compare_numeric_extension(a: &Path, b: &Path) -> Ordering {
-    let a: u32 = a
-        .extension()
-        .expect("One or more paths did not have a valid extension.")
-        .to_string_lossy()
-        .split('.')
-        .find_map(|ext| ext.parse().ok())
-        .expect("One or more paths did not contain a numeric extension.");
-    let b: u32 = b
-        .extension()
-        .expect("One or more paths did not have a valid extension.")
-        .to_string_lossy()
-        .split('.')
-        .find_map(|ext| ext.parse().ok())
-        .expect("One or more paths did not contain a numeric extension.");
+    let a = numeric_extension(a).expect("One or more paths did not contain a numeric extension.");
+    let b = numeric_extension(b).expect("One or more paths did not contain a numeric extension.");
 
     a.cmp(&b)
 }
 
-/// Prompt user for a usize lower than `max`, retrying infinitely.
-#[must_use]
-pub fn prompt_user_for_usize(max: usize) -> usize {
-    print_flush!("Choice: ");
+/// Read a line from `stdin` and remove leading and trailling whitespace.
+///
+/// # Errors
+///
+/// Will return `Err` if `stdin().read_line` fails or `stdin` was closed.
+pub fn prompt() -> Result<String, Error> {
+    let mut result = String::new();
+
+    let read = stdin().read_line(&mut result).map_err(Error::Prompt)?;
+    if read == 0 {
+        return Err(Error::Prompt(io::ErrorKind::UnexpectedEof.into()));
+    }
+
+    Ok(result.trim().to_string())
+}
 
-    let result: Result<usize, _> = prompt().parse();
+/// Read a line from the terminal without showing what is typed, for passwords.
+///
+/// Unlike [`prompt`], whitespace is kept since it can be part of the password.
+///
+/// # Errors
+///
+/// Will return `Err` if the terminal could not be read.
+pub fn prompt_password() -> Result<String, Error> {
+    rpassword::read_password().map_err(Error::Prompt)
+}
 
-    let Ok(result) = result else {
-        return prompt_user_for_usize(max);
+/// The destination to install the apps in `names` into, removing it from `names` if it is one of them.
+///
+/// A `destination` given with `--destination` (`from_option`) is always used, and every name is an app.
+/// Otherwise the last of several names is the destination if it is an existing directory without parts of its own,
+/// since the command line wins over a `destination` from the environment or config,
+/// and the last name is always the destination if there is no `destination`.
+pub fn split_destination(
+    names: &mut Vec<PathBuf>,
+    destination: Option<PathBuf>,
+    from_option: bool,
+) -> Option<PathBuf> {
+    let Some(destination) = destination else {
+        return names.pop();
     };
 
-    if result > max {
-        return prompt_user_for_usize(max);
+    if !from_option && names.len() > 1 && names.last().is_some_and(|name| is_destination(name)) {
+        return names.pop();
     }
 
-    result
+    Some(destination)
 }
 
-/// Prompt user for a path, retrying infinitely.
-#[must_use]
-pub fn prompt_user_for_path(start: &Path) -> PathBuf {
-    print_flush!("Path: {}\\", start.to_string_lossy());
-
-    let path = start.join(PathBuf::from(prompt()));
-
-    let Ok(path) = dunce::canonicalize(path) else {
-        return prompt_user_for_path(start);
+/// Returns true if `path` is a directory without any file named after it, which an app in it would have.
+fn is_destination(path: &Path) -> bool {
+    let Some(name) = path.lossy_file_name() else {
+        return false;
     };
 
-    path
+    fs::read_dir(path).is_ok_and(|mut entries| {
+        !entries.any(|entry| {
+            entry.is_ok_and(|entry| entry.file_name().to_string_lossy().starts_with(&*name))
+        })
+    })
 }
 
-/// Read a line from `stdin` and remove leading and trailling whitespace.
+/// Read a password from the first line of the file at `path`.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Will panic if `stdin().read_line` fails.
-#[must_use]
-pub fn prompt() -> String {
-    let mut result = String::new();
-    stdin()
-        .read_line(&mut result)
-        .expect("Failed to read stdin");
-    result.trim().to_string()
+/// Will return `Err` if the file could not be read.
+pub fn read_password(path: &Path) -> Result<String, Error> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| Error::PasswordFile(path.to_path_buf(), err))?;
+
+    Ok(contents.lines().next().unwrap_or_default().to_string())
 }
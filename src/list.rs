@@ -0,0 +1,91 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{manifest::Manifest, platform, Error, PathExt};
+
+/// An app installed by this tool, as found by [`installed`].
+#[derive(Clone, Debug, Serialize)]
+pub struct InstalledApp {
+    pub name: String,
+    /// The folder the app was installed to.
+    pub path: PathBuf,
+    /// The size of everything in `path` now, which can differ from what was installed.
+    pub size: u64,
+    /// Seconds since the unix epoch the app was installed at.
+    pub installed_at: u64,
+    pub shortcut: Option<PathBuf>,
+    /// Whether the shortcut still exists.
+    pub has_shortcut: bool,
+    /// The file name of the archive the app was installed from.
+    pub source: Option<String>,
+}
+
+impl InstalledApp {
+    fn new(manifest: Manifest) -> Self {
+        // streamed installs have no combined archive, so name them after their first part.
+        let source = manifest
+            .archive
+            .as_deref()
+            .or_else(|| manifest.parts.first().map(PathBuf::as_path))
+            .and_then(|source| source.lossy_file_name())
+            .map(|name| name.into_owned());
+
+        Self {
+            size: dir_size(&manifest.destination).unwrap_or(0),
+            has_shortcut: manifest.shortcut.as_deref().is_some_and(Path::is_file),
+            name: manifest.app_name,
+            path: manifest.destination,
+            installed_at: manifest.installed_at,
+            shortcut: manifest.shortcut,
+            source,
+        }
+    }
+}
+
+/// Find the apps with a manifest in `state_dir`, only those installed into `destination` if given.
+///
+/// # Errors
+///
+/// Will return `Err` if `destination` does not exist, or the manifests could not be read.
+pub fn installed(state_dir: &Path, destination: Option<&Path>) -> Result<Vec<InstalledApp>, Error> {
+    let destination = destination
+        .map(|destination| {
+            dunce::canonicalize(destination)
+                .map_err(|_| Error::MissingDestination(destination.to_path_buf()))
+        })
+        .transpose()?;
+
+    let apps = Manifest::load_all(state_dir)?
+        .into_iter()
+        .filter(|manifest| {
+            destination
+                .as_deref()
+                .is_none_or(|destination| manifest.destination.parent() == Some(destination))
+        })
+        .map(InstalledApp::new)
+        .collect();
+
+    Ok(apps)
+}
+
+/// The total size of the files in `dir`, not following symlinks.
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            platform::file_size(&metadata)
+        };
+    }
+
+    Ok(size)
+}
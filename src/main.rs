@@ -1,29 +1,52 @@
-/// Steps the cli tool takes.
-mod steps;
-
 use std::{
-    env,
-    io::{stderr, Write},
-    panic::{self, PanicHookInfo},
+    collections::HashSet,
+    fs, io,
     path::{Path, PathBuf},
-    process::{exit, Command},
-    time::{Duration, Instant},
+    process::ExitCode,
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 
-use clap::Parser;
-use glob::{glob, Paths};
-use partsinstall::print_flush;
-use steps::{create_destination, create_shortcut, find_final_name, flatten_dir, parse_app_name};
+use clap::{
+    error::ErrorKind, parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser,
+    Subcommand,
+};
+use humansize::{format_size, DECIMAL};
+use partsinstall::{
+    config::{config_path, Config},
+    extract::{ExtractError, ExtractorKind},
+    list::{installed, InstalledApp},
+    manifest, platform, read_password, split_destination,
+    steps::{find_app_names, remove_staging},
+    uninstall::{uninstall, UninstallReport},
+    ArchiveContents, Cleanup, Error, Flatten, InstallPlan, InstallReport, Installer, Interaction,
+};
 
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    /// Name of application in working directory to install
-    name: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Names of applications in working directory to install, then the destination of the install
+    #[arg(value_name = "NAME")]
+    names: Vec<PathBuf>,
 
-    /// Destination of install
-    #[arg(env = "pinst_destination")]
-    destination: PathBuf,
+    /// Destination of install, instead of the last NAME
+    #[arg(short, long, env = "pinst_destination")]
+    destination: Option<PathBuf>,
+
+    /// Install every app with parts or archives in the working directory
+    #[arg(short, long)]
+    all: bool,
+
+    /// File listing the names of applications to install, one per line
+    #[arg(long)]
+    from_file: Option<PathBuf>,
 
     /// Working directory the tool will use
     #[arg(short, long)]
@@ -33,154 +56,622 @@ struct Args {
     #[arg(short = 'S', long)]
     no_shortcut: bool,
 
-    /// Do not flatten installed directories.
+    /// Do not flatten installed directories, the same as `--flatten off`
     #[arg(short = 'F', long)]
     no_flatten: bool,
 
+    /// How inner directories of the install folder are flattened, `keyword` unless the app's profile sets it
+    #[arg(long, value_enum)]
+    flatten: Option<Flatten>,
+
     /// Assume answer that continues execution without interaction on all prompts
-    #[arg(short = 'y', long)]
+    #[arg(short = 'y', long, global = true)]
     no_interaction: bool,
+
+    /// Assume answer that stops execution without interaction on all prompts
+    #[arg(short = 'N', long, global = true, conflicts_with = "no_interaction")]
+    fail_fast: bool,
+
+    /// Stream parts straight into the extractor instead of combining them on disk first
+    #[arg(short, long)]
+    stream: bool,
+
+    /// Extractor to use, instead of the first installed one that supports the archive
+    #[arg(short, long, value_enum)]
+    extractor: Option<ExtractorKind>,
+
+    /// Password to decrypt encrypted archives with, asked for if needed and not given.
+    /// External extractors are passed it on their command line, where other users of the machine can see it
+    #[arg(long, env = "pinst_password", hide_env_values = true)]
+    password: Option<String>,
+
+    /// File to read the password from instead, the first line is used
+    #[arg(long)]
+    password_file: Option<PathBuf>,
+
+    /// What to remove once the app is extracted and flattened
+    #[arg(long, value_enum, default_value_t = Cleanup::None)]
+    cleanup: Cleanup,
+
+    /// Profile to install with, instead of the partsinstall.toml next to the parts
+    #[arg(long)]
+    profile: Option<PathBuf>,
+
+    /// Print what would be installed where, without combining, extracting or writing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Directory install manifests are kept in [default: per-user state directory]
+    #[arg(long, env = "pinst_state_dir", global = true)]
+    state_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Remove an app installed by this tool, and its shortcut
+    Uninstall {
+        /// Name of the installed app
+        name: String,
+    },
+    /// List the apps installed by this tool
+    List {
+        /// Only list apps installed into this folder
+        #[arg(env = "pinst_destination")]
+        destination: Option<PathBuf>,
+
+        /// Print the list as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the defaults read from the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
 }
 
-/// Print only the `payload` on panic.
-fn panic_hook(panic_info: &PanicHookInfo) {
-    if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
-        if writeln!(stderr(), "{s}").is_err() {
-            println!("{s}");
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the value of every option, and whether it comes from the command line, environment, config or default
+    Show,
+}
+
+/// The command line interface, with the settings in `config` as the defaults of the options they name.
+///
+/// Defaults are only used when an option is not given on the command line or in the environment.
+fn command(config: &Config) -> Result<clap::Command, Error> {
+    let mut command = Args::command();
+
+    for (key, values) in config.iter() {
+        let is_option = command
+            .get_arguments()
+            .any(|arg| arg.get_id() == key && arg.get_long().is_some());
+
+        if !is_option {
+            return Err(Error::Config(
+                config.path.clone().unwrap_or_default(),
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("`{key}` is not an option"),
+                ),
+            ));
         }
-    } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
-        if writeln!(stderr(), "{s}").is_err() {
-            println!("{s}");
+
+        command = command.mut_arg(key, |arg| arg.default_values(values.to_vec()));
+
+        // subcommands with an argument of the same name, like `list`'s destination, use the default too.
+        let subcommands: Vec<String> = command
+            .get_subcommands()
+            .filter(|sub| sub.get_arguments().any(|arg| arg.get_id() == key))
+            .map(|sub| sub.get_name().to_string())
+            .collect();
+        for name in subcommands {
+            command = command.mut_subcommand(name, |sub| {
+                sub.mut_arg(key, |arg| arg.default_values(values.to_vec()))
+            });
         }
-    } else {
-        let s = "Panic occurred";
-        if writeln!(stderr(), "{s}").is_err() {
-            println!("{s}");
+    }
+
+    Ok(command)
+}
+
+/// Print the value of every option in `matches` and where it came from, as it would be written in the config file.
+fn print_config(matches: &ArgMatches, config: &Config) {
+    match (&config.path, config_path()) {
+        (Some(path), _) => println!("# Config file: {path:?}"),
+        (None, Some(path)) => println!("# Config file: {path:?}, which does not exist"),
+        (None, None) => println!("# Config file: none, there is no config directory"),
+    }
+
+    let mut lines = Vec::new();
+    // setting defaults moves the options to the end, so they are listed in the order they are declared in.
+    for arg in Args::command().get_arguments() {
+        let id = arg.get_id().as_str();
+        if arg.get_long().is_none() {
+            continue;
+        }
+
+        let Some(values) = matches.get_raw(id) else {
+            lines.push((format!("# {id} is not set"), String::new()));
+            continue;
+        };
+
+        let values: Vec<String> = values
+            .map(|value| {
+                let value = value.to_string_lossy();
+                if arg.is_hide_env_values_set() {
+                    "\"<hidden>\"".to_string()
+                } else if arg.get_action().takes_values() {
+                    format!("{value:?}")
+                } else {
+                    value.into_owned()
+                }
+            })
+            .collect();
+
+        let source = match matches.value_source(id) {
+            Some(ValueSource::CommandLine) => "command line".to_string(),
+            Some(ValueSource::EnvVariable) => format!(
+                "environment {}",
+                arg.get_env().unwrap_or_default().to_string_lossy()
+            ),
+            Some(ValueSource::DefaultValue) if config.get(id).is_some() => "config".to_string(),
+            _ => "default".to_string(),
+        };
+
+        let value = match values.as_slice() {
+            [value] => value.clone(),
+            values => format!("[{}]", values.join(", ")),
+        };
+        lines.push((format!("{id} = {value}"), source));
+    }
+
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+    for (line, source) in lines {
+        if source.is_empty() {
+            println!("{line}");
+        } else {
+            println!("{line:width$}  # {source}");
         }
     }
 }
 
-/// Print summary and exit with exit code 0
-fn success(
-    combine_time: Duration,
-    extract_time: Duration,
-    flatten_time: Duration,
-    start: Instant,
-) -> ! {
+/// Print summary.
+fn success(report: &InstallReport, start: Instant) {
+    if let Some(manifest) = &report.manifest {
+        println!("\nRecorded install in {manifest:?}");
+    }
+
+    if !report.cleaned.is_empty() {
+        println!(
+            "\nCleaned up {} file(s), reclaiming {}.",
+            report.cleaned.len(),
+            format_size(report.reclaimed, DECIMAL)
+        );
+    }
+
     println!(
-        "\nDone! (combining took {combine_time:?}, extracting took {extract_time:?}, flattening took {flatten_time:?}, total: {:?})",
+        "\nDone! (combining took {:?}, extracting took {:?}, flattening took {:?}, total: {:?})",
+        report.combine_time,
+        report.extract_time,
+        report.flatten_time,
         start.elapsed()
     );
-
-    exit(0)
 }
 
-fn main() {
-    let start = Instant::now();
+/// Print what an install would do.
+fn print_plan(plan: &InstallPlan) {
+    println!("Dry run, nothing will be combined, extracted or written.\n");
+
+    println!("App name:    {}", plan.app_name);
+    if let Some(profile) = &plan.profile {
+        println!("Profile:     {profile:?}");
+    }
+    println!("Parts:       {} ({})", plan.parts.len(), plan.volume_kind);
+    for part in &plan.parts {
+        println!("  {part:?}");
+    }
+    if !plan.checksum_files.is_empty() {
+        println!("Checksums:   {:?}", plan.checksum_files);
+    }
+
+    match &plan.archive {
+        Some(archive) if plan.combine => {
+            println!("Archive:     {archive:?}, combined from the parts")
+        }
+        Some(archive) => println!("Archive:     {archive:?}"),
+        None => println!("Archive:     streamed from the parts"),
+    }
+    println!("Extractor:   {}", plan.extractor);
+
+    if plan.destination.exists() {
+        println!("Destination: {:?}, which already exists", plan.destination);
+    } else {
+        println!("Destination: {:?}", plan.destination);
+    }
 
-    let args = Args::parse();
+    match &plan.contents {
+        ArchiveContents::Listed {
+            entries,
+            inner_dir,
+            executable,
+        } => {
+            println!("Contents:    {entries} file(s) and folder(s)");
+            match inner_dir {
+                Some(inner_dir) => println!("Flatten:     {inner_dir:?}"),
+                None if plan.flatten != Flatten::Off => println!("Flatten:     no inner directory"),
+                None => println!("Flatten:     off"),
+            }
+            match executable {
+                Some(executable) => println!("Shortcut to: {executable:?}"),
+                None if plan.shortcut => {
+                    println!("Shortcut to: no executable found, you would be asked for one");
+                }
+                None => println!("Shortcut to: off"),
+            }
+        }
+        ArchiveContents::Unknown(reason) => {
+            println!("Contents:    unknown, {reason}");
+        }
+    }
 
-    panic::set_hook(Box::new(panic_hook));
+    for command in &plan.post_install {
+        println!("Then run:    {command}");
+    }
+}
 
-    assert!(
-        args.destination.exists(),
-        "Destination {:?} does not exist.",
-        args.destination
+/// Print uninstall summary.
+fn uninstalled(report: &UninstallReport) {
+    println!(
+        "\nRemoved {} files ({}) from {:?}.",
+        report.removed_files,
+        format_size(report.removed_size, DECIMAL),
+        report.destination
     );
 
-    if let Some(working_dir) = args.working_dir {
-        assert!(
-            working_dir.exists(),
-            "Working directory {working_dir:?} does not exist."
-        );
+    if report.missing_files > 0 {
+        println!("{} files were already gone.", report.missing_files);
+    }
+    if !report.kept.is_empty() {
+        println!("Kept {} files that were not installed.", report.kept.len());
+    }
+    if let Some(shortcut) = &report.shortcut {
+        println!("Removed shortcut {shortcut:?}.");
+    }
 
-        env::set_current_dir(&working_dir).expect("Could not set working directory.");
-        println!("Using working directory: {working_dir:?}.\n");
+    println!("\nDone! {} was uninstalled.", report.app_name);
+}
+
+/// Print the installed `apps` as a table.
+fn print_installed(apps: &[InstalledApp]) {
+    if apps.is_empty() {
+        println!("No apps installed.");
+        return;
     }
 
-    let Some(app_name) = parse_app_name(&args.name) else {
-        println!("Could not parse app name.");
-        exit(1);
-    };
-    println!("Parsed name as: {app_name}\n");
+    let rows: Vec<[String; 6]> = apps
+        .iter()
+        .map(|app| {
+            let installed_at = UNIX_EPOCH + Duration::from_secs(app.installed_at);
+
+            [
+                app.name.clone(),
+                format_size(app.size, DECIMAL),
+                humantime::format_rfc3339_seconds(installed_at).to_string(),
+                if app.has_shortcut { "yes" } else { "no" }.to_string(),
+                app.source.clone().unwrap_or_default(),
+                platform::display_path(&app.path).into_owned(),
+            ]
+        })
+        .collect();
+
+    let header = ["Name", "Size", "Installed", "Shortcut", "Source", "Path"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
 
-    let glob_pattern = format!("{app_name}*");
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+}
 
-    let files: Paths = if args.name.is_dir() {
-        glob(
-            &Path::new(app_name.as_ref())
-                .join(&glob_pattern)
-                .to_string_lossy(),
-        )
-        .expect("Glob pattern was not valid")
+/// List the apps with a manifest in `state_dir`.
+fn list(state_dir: Option<&Path>, destination: Option<&Path>, json: bool) -> Result<(), Error> {
+    let apps = match state_dir {
+        Some(state_dir) => installed(state_dir, destination)?,
+        None => Vec::new(),
+    };
+
+    if json {
+        // serializing plain structs cannot fail.
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&apps).unwrap_or_default()
+        );
     } else {
-        glob(&glob_pattern).expect("Glob pattern was not valid")
+        print_installed(&apps);
+    }
+
+    Ok(())
+}
+
+/// Map `err` to the exit code the process ends with, distinct for every variant.
+fn exit_code(err: &Error) -> u8 {
+    match err {
+        Error::Cancelled => 1,
+        Error::MissingDestination(_) => 2,
+        Error::WorkingDir(..) => 3,
+        Error::AppName(_) => 4,
+        Error::Pattern(_) => 5,
+        Error::NoFilesFound(_) => 6,
+        Error::UnknownExtension => 7,
+        Error::Prompt(_) => 8,
+        Error::NotNumbered(_) => 9,
+        Error::Combine(..) => 10,
+        Error::Destination(..) => 11,
+        Error::NoExtractor(_) => 12,
+        Error::NoStreamExtractor(_) => 13,
+        Error::ChecksumFile(..) => 14,
+        Error::Checksum(..) => 15,
+        Error::Verify(..) => 16,
+        Error::MissingParts(_) => 17,
+        Error::DuplicateParts(_) => 18,
+        Error::MixedPadding(_) => 19,
+        Error::Extract(err) => match err {
+            ExtractError::NotFound(_) => 20,
+            ExtractError::Unsupported(..) => 21,
+            ExtractError::Io(_) => 22,
+            ExtractError::Fatal(_) => 23,
+            ExtractError::Corrupt(_) => 24,
+            ExtractError::WrongPassword(_) => 25,
+            ExtractError::CommandLine(_) => 26,
+            ExtractError::OutOfMemory(_) => 27,
+            ExtractError::Write(_) => 28,
+            ExtractError::UserStopped(_) => 29,
+            ExtractError::UnknownExitCode(..) => 30,
+        },
+        Error::PasswordFile(..) => 31,
+        Error::Flatten(..) => 40,
+        Error::Shortcut(_) => 41,
+        Error::Manifest(..) => 42,
+        Error::Hook(..) => 43,
+        Error::NotInstalled(_) => 50,
+        Error::OutsideDestination(_) => 51,
+        Error::Uninstall(..) => 52,
+        Error::AppList(..) => 60,
+        Error::Batch(..) => 61,
+        Error::Profile(..) => 70,
+        Error::Config(..) => 71,
+    }
+}
+
+/// Read the names of the apps to install from the file at `path`, skipping blank lines and `#` comments.
+fn read_app_list(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| Error::AppList(path.to_path_buf(), err))?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Split the destination from the names given, exiting with a usage error if either is missing.
+///
+/// See [`split_destination`] for when the last name is the destination.
+fn destination(
+    mut names: Vec<PathBuf>,
+    destination: Option<PathBuf>,
+    from_option: bool,
+    batch: bool,
+) -> (Vec<PathBuf>, PathBuf) {
+    let Some(destination) = split_destination(&mut names, destination, from_option) else {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the destination was not given, add it after the names or set pinst_destination",
+            )
+            .exit();
     };
 
-    let mut files: Vec<PathBuf> = files.filter_map(Result::ok).collect();
+    if names.is_empty() && !batch {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "no app to install was given, name one or use --all or --from-file",
+            )
+            .exit();
+    }
 
-    if files.is_empty() {
-        println!("No files were found starting with the name {app_name}");
-        exit(1);
+    (names, destination)
+}
+
+/// Print which of a batch of apps were installed, given the `results` of installing each of them.
+fn print_batch(results: &[(PathBuf, Result<(), Error>)], dry_run: bool) {
+    println!("Summary:");
+
+    for (name, result) in results {
+        match result {
+            Ok(()) if dry_run => println!("  planned  {name:?}"),
+            Ok(()) => println!("  ok       {name:?}"),
+            Err(err) => println!("  failed   {name:?}: {err}"),
+        }
     }
+}
 
-    let (final_name, combine_time) = find_final_name(&app_name, &mut files, args.no_interaction);
+/// Install the apps named in `args`, one after another.
+///
+/// When more than one app is installed, a failed app does not stop the others,
+/// and `Error::Batch` is returned once all of them were tried.
+fn install(
+    args: Args,
+    destination_from_option: bool,
+    interaction: Interaction,
+) -> Result<(), Error> {
+    let batch = args.all || args.from_file.is_some();
+    let (mut names, destination) =
+        destination(args.names, args.destination, destination_from_option, batch);
+
+    if let Some(path) = &args.from_file {
+        names.extend(read_app_list(path)?);
+    }
+    if args.all {
+        let working_dir = args.working_dir.as_deref().unwrap_or(Path::new("."));
+        names.extend(find_app_names(working_dir)?.into_iter().map(PathBuf::from));
+    }
 
-    let destination = args.destination.join(app_name.as_ref());
-    println!("\nExtracting {app_name} to {destination:?}");
+    // an app named more than once is only installed once.
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
 
-    create_destination(&destination, args.no_interaction);
+    if names.is_empty() {
+        println!("No apps to install were found.");
+        return Ok(());
+    }
 
-    let destination_str = destination.to_string_lossy();
-    let destination_arg = format!("-o{destination_str}");
+    let password = match args.password_file {
+        Some(path) => Some(read_password(&path)?),
+        None => args.password,
+    };
 
-    let sevenzip_args: &[&str] = if args.no_interaction {
-        print_flush!("\n7z using -y");
-        // x - extract with full paths (https://documentation.help/7-Zip/extract_full.htm)
-        &["x", &destination_arg, "-y", &final_name]
+    let mut installer = Installer::new(PathBuf::new(), destination)
+        .shortcut(!args.no_shortcut)
+        .stream(args.stream)
+        .extractor(args.extractor)
+        .password(password)
+        .cleanup(args.cleanup)
+        .profile(args.profile)
+        .interaction(interaction);
+
+    // a strategy that is not given is left to the profile.
+    let flatten = if args.no_flatten {
+        Some(Flatten::Off)
     } else {
-        &["x", &destination_arg, &final_name]
+        args.flatten
     };
+    if let Some(flatten) = flatten {
+        installer = installer.flatten(flatten);
+    }
+
+    if let Some(working_dir) = args.working_dir {
+        println!("Using working directory: {working_dir:?}.\n");
+        installer = installer.working_dir(working_dir);
+    }
 
-    let extract_start = Instant::now();
-    let sevenzip = Command::new("7z")
-        .args(sevenzip_args)
-        .status()
-        .expect("Could not run 7z");
+    if let Some(state_dir) = args.state_dir {
+        installer = installer.manifest_dir(Some(state_dir));
+    }
 
-    println!();
+    let mut results = Vec::new();
+    for (n, name) in names.iter().enumerate() {
+        if names.len() > 1 {
+            println!("==> Installing {name:?} ({} of {})\n", n + 1, names.len());
+        }
 
-    // found here: https://documentation.help/7-Zip/exit_codes.htm
-    match sevenzip.code().expect("Could not determine 7z's exit code") {
-        // ok (no error or warning)
-        0 | 1 => {}
-        2 => panic!("7z encounted a fatal error"),
-        7 => panic!("7z: command line error"),
-        8 => panic!("7z: not enough memory for operation"),
-        255 => panic!("7z: user stopped the process"),
-        code => panic!("Unknown 7z exit code {code} encountered"),
+        let start = Instant::now();
+        installer = installer.name(name);
+
+        let result = if args.dry_run {
+            installer.plan().map(|plan| print_plan(&plan))
+        } else {
+            installer.run().map(|report| success(&report, start))
+        };
+
+        if names.len() == 1 {
+            return result;
+        }
+
+        if let Err(err) = &result {
+            eprintln!("{err}");
+        }
+        println!();
+        results.push((name.clone(), result));
     }
 
-    let extract_time = extract_start.elapsed();
+    print_batch(&results, args.dry_run);
 
-    let flatten_start = Instant::now();
-    if args.no_flatten {
-        println!("Not flattening install directory.");
-    } else {
-        flatten_dir(&app_name, &destination);
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed > 0 {
+        return Err(Error::Batch(failed, results.len()));
     }
-    let flatten_time = flatten_start.elapsed();
 
-    if args.no_shortcut {
-        println!("Not creating start menu shortcut.");
-    } else if env::consts::OS == "windows" {
-        println!("Creating start menu shortcut:");
-        
-        create_shortcut(&app_name, &destination, args.no_interaction);
-        success(combine_time, extract_time, flatten_time, start);
+    Ok(())
+}
+
+/// Parse the command line, with the defaults in `config`, and run the command given.
+fn run(config: &Config) -> Result<(), Error> {
+    let matches = command(config)?.get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    let given = |id: &str| {
+        matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
+    };
+    // either can be set in the config, and is then overridden by the other given on the command line.
+    let interaction = if args.no_interaction && !(args.fail_fast && given("fail_fast")) {
+        Interaction::AssumeYes
+    } else if args.fail_fast {
+        Interaction::AssumeNo
     } else {
-        println!("Not creating start menu shortcuts, not on Windows.");
+        Interaction::Prompt
+    };
+
+    let state_dir = args.state_dir.clone().or_else(manifest::state_dir);
+
+    match &args.command {
+        Some(Command::Uninstall { name }) => state_dir
+            .ok_or_else(|| Error::NotInstalled(name.clone()))
+            .and_then(|state_dir| uninstall(name, &state_dir, interaction.interactor().as_ref()))
+            .map(|report| uninstalled(&report)),
+        Some(Command::List { destination, json }) => {
+            list(state_dir.as_deref(), destination.as_deref(), *json)
+        }
+        Some(Command::Config {
+            action: ConfigAction::Show,
+        }) => {
+            print_config(&matches, config);
+            Ok(())
+        }
+        None => install(
+            args,
+            matches.value_source("destination") == Some(ValueSource::CommandLine),
+            interaction,
+        ),
+    }
+}
+
+fn main() -> ExitCode {
+    // the staging folder of an interrupted install would be left next to its destination.
+    let handler = ctrlc::set_handler(|| {
+        eprintln!("\nInterrupted, removing unfinished installs.");
+        remove_staging();
+        std::process::exit(130);
+    });
+    if let Err(err) = handler {
+        eprintln!("Could not handle Ctrl-C: {err}");
     }
 
-    success(combine_time, extract_time, flatten_time, start);
+    let result = config_path()
+        .map_or_else(|| Ok(Config::default()), |path| Config::load(&path))
+        .and_then(|config| run(&config));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(exit_code(&err))
+        }
+    }
 }
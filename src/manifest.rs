@@ -0,0 +1,225 @@
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Error, InstallReport, PathExt};
+
+/// A record of what an install wrote, kept so it can be uninstalled later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The version of partsinstall that wrote the manifest.
+    pub version: String,
+    pub app_name: String,
+    /// The parts the app was installed from.
+    pub parts: Vec<PathBuf>,
+    /// The archive given to the extractor, `None` if the parts were streamed.
+    pub archive: Option<PathBuf>,
+    /// The folder the app was installed to.
+    pub destination: PathBuf,
+    /// The name of `destination`, which a profile can set to something other than the app name.
+    /// `None` in manifests written before it was recorded, when it was always the app name.
+    pub folder: Option<String>,
+    /// Every file the install wrote into `destination`.
+    pub files: Vec<FileRecord>,
+    pub shortcut: Option<PathBuf>,
+    /// The name of `shortcut` without its extension, `None` if there is no shortcut
+    /// or in manifests written before it was recorded, when it was always the app name.
+    pub shortcut_name: Option<String>,
+    pub flags: Flags,
+    /// Seconds since the unix epoch the install finished at.
+    pub installed_at: u64,
+}
+
+/// A file written by an install.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRecord {
+    /// The path of the file, relative to the install's destination.
+    pub path: PathBuf,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the file's contents.
+    pub sha256: String,
+}
+
+/// The options an install was run with.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flags {
+    pub flatten: bool,
+    /// How inner directories were flattened, `None` in manifests written before it was recorded.
+    pub flatten_strategy: Option<String>,
+    pub shortcut: bool,
+    pub stream: bool,
+    /// The extractor asked for, `None` if it was probed for.
+    pub extractor: Option<String>,
+    /// The profile the install was run with, if any.
+    pub profile: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// The manifest of `report`'s install, which wrote `files`, see [`record_files`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the destination could not be found.
+    pub fn new(
+        report: &InstallReport,
+        files: Vec<FileRecord>,
+        flags: Flags,
+    ) -> Result<Self, Error> {
+        let destination = dunce::canonicalize(&report.destination)
+            .map_err(|err| Error::Manifest(report.destination.clone(), err))?;
+
+        let installed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+
+        Ok(Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            app_name: report.app_name.clone(),
+            parts: report.parts.clone(),
+            archive: report.archive.clone(),
+            folder: destination.lossy_file_name().map(|name| name.into_owned()),
+            destination,
+            files,
+            shortcut_name: report
+                .shortcut
+                .as_deref()
+                .and_then(|shortcut| shortcut.lossy_file_stem())
+                .map(|name| name.into_owned()),
+            shortcut: report.shortcut.clone(),
+            flags,
+            installed_at,
+        })
+    }
+
+    /// The path of the manifest for `app_name` in `dir`.
+    #[must_use]
+    pub fn path(dir: &Path, app_name: &str) -> PathBuf {
+        dir.join(format!("{app_name}.json"))
+    }
+
+    /// Write the manifest into `dir`, replacing any earlier manifest of the same app.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `dir` could not be created or the manifest could not be written.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, Error> {
+        let path = Self::path(dir, &self.app_name);
+
+        fs::create_dir_all(dir).map_err(|err| Error::Manifest(dir.to_path_buf(), err))?;
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|err| Error::Manifest(path.clone(), err.into()))?;
+        fs::write(&path, json).map_err(|err| Error::Manifest(path.clone(), err))?;
+
+        Ok(path)
+    }
+
+    /// Read the manifest at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` could not be read or is not a manifest.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let json = fs::read(path).map_err(|err| Error::Manifest(path.to_path_buf(), err))?;
+
+        serde_json::from_slice(&json).map_err(|err| Error::Manifest(path.to_path_buf(), err.into()))
+    }
+
+    /// Read every manifest in `dir`, sorted by app name. A missing `dir` has no manifests.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `dir` or one of the manifests in it could not be read.
+    pub fn load_all(dir: &Path) -> Result<Vec<Self>, Error> {
+        let entries = match dir.read_dir() {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(Error::Manifest(dir.to_path_buf(), err)),
+        };
+
+        let mut manifests = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| Error::Manifest(dir.to_path_buf(), err))?
+                .path();
+
+            if path.extension().is_some_and(|ext| ext == "json") {
+                manifests.push(Self::load(&path)?);
+            }
+        }
+        manifests.sort_by(|a, b| a.app_name.cmp(&b.app_name));
+
+        Ok(manifests)
+    }
+}
+
+/// The per-user directory manifests are kept in.
+///
+/// `$XDG_STATE_HOME/partsinstall` on linux, `%LOCALAPPDATA%\partsinstall` on windows.
+#[must_use]
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("partsinstall"))
+}
+
+/// Record every file in `dir`, sorted by path relative to `dir`.
+///
+/// Installs record the staging folder they were extracted to, so files that were already in the destination
+/// are not recorded, and are never removed by an uninstall.
+///
+/// # Errors
+///
+/// Will return `Err` if `dir` could not be read, or a file in it could not be hashed.
+pub fn record_files(dir: &Path) -> Result<Vec<FileRecord>, Error> {
+    let mut files = Vec::new();
+    record_dir(dir, dir, &mut files).map_err(|err| Error::Manifest(dir.to_path_buf(), err))?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(files)
+}
+
+/// Add a record of every file under `dir` to `files`, with paths relative to `root`.
+fn record_dir(root: &Path, dir: &Path, files: &mut Vec<FileRecord>) -> io::Result<()> {
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            record_dir(root, &path, files)?;
+        } else if file_type.is_file() {
+            let (size, sha256) = hash_file(&path)?;
+
+            files.push(FileRecord {
+                // every path read from `dir` starts with `root`.
+                path: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                size,
+                sha256,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the size and hex SHA-256 of the file at `path`.
+fn hash_file(path: &Path) -> io::Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    let mut hex = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        // writing to a String cannot fail.
+        let _ = write!(hex, "{byte:02x}");
+    }
+
+    Ok((size, hex))
+}
@@ -0,0 +1,128 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+/// A reader over `parts`, concatenated in order, that never writes the combined file to disk.
+///
+/// Seeking is supported, so formats that need random access (zip, 7z) can be read too.
+pub struct PartsReader {
+    parts: Vec<PathBuf>,
+    /// Offset in the combined stream each part starts at, plus the total length at the end.
+    offsets: Vec<u64>,
+    pos: u64,
+    /// The currently open part, and whether its position is in sync with `pos`.
+    current: Option<(usize, File, bool)>,
+}
+
+impl PartsReader {
+    /// Create a reader over `parts`, which must already be sorted.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the metadata of any part could not be read.
+    pub fn open(parts: &[PathBuf]) -> io::Result<Self> {
+        let mut offsets = Vec::with_capacity(parts.len() + 1);
+        let mut offset = 0;
+
+        for part in parts {
+            offsets.push(offset);
+            offset += fs::metadata(part)?.len();
+        }
+        offsets.push(offset);
+
+        Ok(Self {
+            parts: parts.to_vec(),
+            offsets,
+            pos: 0,
+            current: None,
+        })
+    }
+
+    /// Total length of all parts combined.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.offsets.last().copied().unwrap_or(0)
+    }
+
+    /// Returns true if there are no bytes to read.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The index of the part containing `pos`.
+    fn part_at(&self, pos: u64) -> usize {
+        // offsets is sorted, and the first part starts at 0.
+        self.offsets.partition_point(|&offset| offset <= pos) - 1
+    }
+}
+
+impl Read for PartsReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len() {
+            return Ok(0);
+        }
+
+        let index = self.part_at(self.pos);
+        let part_start = self.offsets[index];
+        let part_end = self.offsets[index + 1];
+
+        let file = match &mut self.current {
+            Some((current, file, synced)) if *current == index => {
+                if !*synced {
+                    file.seek(SeekFrom::Start(self.pos - part_start))?;
+                    *synced = true;
+                }
+                file
+            }
+            current => {
+                let mut file = File::open(&self.parts[index])?;
+                file.seek(SeekFrom::Start(self.pos - part_start))?;
+                &mut current.insert((index, file, true)).1
+            }
+        };
+
+        // do not read past the end of the part, in case it grew since we opened it.
+        let remaining = usize::try_from(part_end - self.pos).unwrap_or(usize::MAX);
+        let len = buf.len().min(remaining);
+
+        let read = file.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{:?} is shorter than expected", self.parts[index]),
+            ));
+        }
+
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for PartsReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        let Some(new_pos) = new_pos else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        if new_pos != self.pos {
+            self.pos = new_pos;
+            if let Some((_, _, synced)) = &mut self.current {
+                *synced = false;
+            }
+        }
+
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    borrow::Cow,
+    env,
+    fmt::Write as _,
+    fs::{self, File, Metadata},
+    io::{self, Read},
+    os::unix::fs::{FileExt, MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{name_has_keywords, Error, PathExt};
+
+/// Magic bytes at the start of every ELF binary.
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+/// Extension of the launchers [`create_shortcut`] creates.
+pub const SHORTCUT_EXT: &str = "desktop";
+
+/// Image extensions we look for when choosing a launcher icon.
+const ICON_EXTS: &[&str] = &["png", "svg", "xpm"];
+
+/// How much [`copy_range`] asks the kernel to copy at once, so progress can be counted between calls.
+#[cfg(target_os = "linux")]
+const COPY_RANGE_CHUNK: u64 = 64 * 1024 * 1024;
+
+/// Get the size of a file from its `metadata`.
+#[must_use]
+pub fn file_size(metadata: &Metadata) -> u64 {
+    metadata.size()
+}
+
+/// Reserve `len` bytes on disk for `file` and set its size to `len`,
+/// so writing into it at any offset cannot run out of space part way.
+///
+/// Filesystems that cannot reserve space only have the size set.
+///
+/// # Errors
+///
+/// Will return `Err` if there is not enough space, or the file could not be resized.
+pub fn preallocate(file: &File, len: u64) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    if let Ok(len) = libc::off_t::try_from(len) {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: the file descriptor belongs to `file`, which is open for the whole call.
+        if unsafe { libc::fallocate(file.as_raw_fd(), 0, 0, len) } == 0 {
+            return Ok(());
+        }
+
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EOPNOTSUPP) {
+            return Err(err);
+        }
+    }
+
+    file.set_len(len)
+}
+
+/// Copy `len` bytes of `from` at `from_offset` into `to` at `to_offset` with `copy_file_range`,
+/// which never copies through userspace, and shares the data instead on filesystems with reflinks (btrfs, xfs).
+/// `copied` is called with the number of bytes copied after each chunk.
+///
+/// Returns `false` without copying anything if the kernel cannot copy between the files,
+/// eg. when they are on different filesystems of an old kernel.
+///
+/// # Errors
+///
+/// Will return `Err` if copying fails part way, or `from` ends early.
+#[cfg(target_os = "linux")]
+pub fn copy_range(
+    from: &File,
+    from_offset: u64,
+    to: &File,
+    to_offset: u64,
+    len: u64,
+    mut copied: impl FnMut(u64),
+) -> io::Result<bool> {
+    use std::os::fd::AsRawFd;
+
+    let (Ok(mut from_offset), Ok(mut to_offset)) = (
+        libc::loff_t::try_from(from_offset),
+        libc::loff_t::try_from(to_offset),
+    ) else {
+        return Ok(false);
+    };
+
+    let mut left = len;
+    while left > 0 {
+        // SAFETY: both file descriptors belong to files open for the whole call,
+        // and the offsets are valid for the kernel to update.
+        let result = unsafe {
+            libc::copy_file_range(
+                from.as_raw_fd(),
+                &mut from_offset,
+                to.as_raw_fd(),
+                &mut to_offset,
+                left.min(COPY_RANGE_CHUNK) as usize,
+                0,
+            )
+        };
+
+        match u64::try_from(result) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                left -= n;
+                copied(n);
+            }
+            Err(_) => {
+                let err = io::Error::last_os_error();
+                let unsupported = matches!(
+                    err.raw_os_error(),
+                    Some(
+                        libc::EXDEV | libc::ENOSYS | libc::EINVAL | libc::EOPNOTSUPP | libc::EPERM
+                    )
+                );
+
+                if unsupported && left == len {
+                    return Ok(false);
+                } else if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+/// Only linux can copy between offsets of open files in the kernel,
+/// so this never copies anything and returns `false`.
+///
+/// # Errors
+///
+/// Never returns `Err`, it is only fallible to match linux.
+#[cfg(not(target_os = "linux"))]
+pub fn copy_range(
+    _from: &File,
+    _from_offset: u64,
+    _to: &File,
+    _to_offset: u64,
+    _len: u64,
+    _copied: impl FnMut(u64),
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Write all of `buf` into `file` at `offset`.
+///
+/// # Errors
+///
+/// Will return `Err` if the write fails.
+pub fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    file.write_all_at(buf, offset)
+}
+
+/// Display `path` the way the user would type it.
+#[must_use]
+pub fn display_path(path: &Path) -> Cow<'_, str> {
+    path.to_string_lossy()
+}
+
+/// Returns true if `path` looks like something a shortcut can be made to.
+///
+/// Archives often lose the executable bit, so `AppImage`s and ELF binaries are also accepted.
+#[must_use]
+pub fn is_executable(path: &Path) -> bool {
+    if fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0) {
+        return true;
+    }
+
+    if path
+        .lossy_extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"))
+    {
+        return true;
+    }
+
+    let mut magic = [0; 4];
+    File::open(path).is_ok_and(|mut file| file.read_exact(&mut magic).is_ok())
+        && &magic == ELF_MAGIC
+}
+
+/// Returns true if the archive entry `path`, with the unix permissions `mode`, would be extracted as an executable.
+///
+/// Its contents are not read, so unlike [`is_executable`] ELF binaries that lost the executable bit are not found.
+#[must_use]
+pub fn is_executable_entry(path: &Path, mode: Option<u32>) -> bool {
+    mode.is_some_and(|mode| mode & 0o111 != 0)
+        || path
+            .lossy_extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"))
+}
+
+/// A command running `command` with `sh`.
+#[must_use]
+pub fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+/// Create a freedesktop `.desktop` launcher called `app_name` to `executable` run with `args`
+/// in `$XDG_DATA_HOME/applications`, returning the path of the launcher.
+///
+/// # Errors
+///
+/// Will return `Err` if neither `XDG_DATA_HOME` nor `HOME` are set, or the launcher could not be written.
+pub fn create_shortcut(
+    app_name: &str,
+    executable: &Path,
+    args: &[String],
+    working_dir: &Path,
+) -> Result<PathBuf, Error> {
+    let Some(applications) = applications_dir() else {
+        return Err(Error::Shortcut(
+            "Could not find environment variable XDG_DATA_HOME or HOME".to_string(),
+        ));
+    };
+
+    fs::create_dir_all(&applications)
+        .map_err(|err| Error::Shortcut(format!("Could not create {applications:?}: {err}")))?;
+
+    let icon = find_icon(app_name, working_dir);
+    let icon = icon.as_deref().map_or(
+        Cow::Borrowed("application-x-executable"),
+        Path::to_string_lossy,
+    );
+
+    let entry = desktop_entry(app_name, executable, args, working_dir, &icon);
+    let shortcut = applications.join(format!("{app_name}.{SHORTCUT_EXT}"));
+
+    fs::write(&shortcut, entry)
+        .map_err(|err| Error::Shortcut(format!("Could not write {shortcut:?}: {err}")))?;
+
+    println!("Successfully created shortcut to {executable:?}.");
+    Ok(shortcut)
+}
+
+/// `$XDG_DATA_HOME/applications`, falling back to `~/.local/share/applications`.
+fn applications_dir() -> Option<PathBuf> {
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+
+    Some(data_home.join("applications"))
+}
+
+/// Find an image in `dir` to use as the launcher icon, preferring ones named after the app.
+fn find_icon(app_name: &str, dir: &Path) -> Option<PathBuf> {
+    let mut icons: Vec<PathBuf> = dir
+        .read_dir()
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.lossy_extension()
+                .is_some_and(|ext| ICON_EXTS.contains(&ext.to_lowercase().as_ref()))
+        })
+        .collect();
+    icons.sort();
+
+    let chosen = icons
+        .iter()
+        .position(|icon| name_has_keywords(app_name.split(' '), icon))
+        .unwrap_or(0);
+
+    (chosen < icons.len()).then(|| icons.swap_remove(chosen))
+}
+
+/// Build the contents of a `.desktop` file.
+///
+/// <https://specifications.freedesktop.org/desktop-entry-spec/latest/>
+#[must_use]
+pub fn desktop_entry(
+    app_name: &str,
+    executable: &Path,
+    args: &[String],
+    working_dir: &Path,
+    icon: &str,
+) -> String {
+    let mut entry = String::from("[Desktop Entry]\nType=Application\n");
+
+    let exec: Vec<String> = std::iter::once(executable.to_string_lossy())
+        .chain(args.iter().map(|arg| Cow::Borrowed(arg.as_str())))
+        .map(|arg| quote_exec_arg(&arg))
+        .collect();
+
+    // writing to a String cannot fail.
+    let _ = writeln!(entry, "Name={}", escape_value(app_name));
+    let _ = writeln!(entry, "Exec={}", escape_value(&exec.join(" ")));
+    let _ = writeln!(
+        entry,
+        "Path={}",
+        escape_value(&working_dir.to_string_lossy())
+    );
+    let _ = writeln!(entry, "Icon={}", escape_value(icon));
+    entry.push_str("Terminal=false\n");
+
+    entry
+}
+
+/// Quote an argument of the `Exec` key, escaping the characters the spec reserves.
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            // field codes start with %, so a literal % must be doubled.
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Escape a string value of a desktop entry key.
+fn escape_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('\n', r"\n")
+        .replace('\t', r"\t")
+        .replace('\r', r"\r")
+}
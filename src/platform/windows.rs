@@ -0,0 +1,165 @@
+use std::{
+    borrow::Cow,
+    fs::{File, Metadata},
+    io,
+    os::windows::{
+        fs::{FileExt, MetadataExt},
+        process::CommandExt,
+    },
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{Error, PathExt};
+
+/// Extension of the shortcuts [`create_shortcut`] creates.
+pub const SHORTCUT_EXT: &str = "lnk";
+
+/// Returns true if `path` looks like something a shortcut can be made to.
+#[must_use]
+pub fn is_executable(path: &Path) -> bool {
+    path.lossy_extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+/// Returns true if the archive entry `path` would be extracted as an executable.
+///
+/// Windows has no permissions to go by, so `_mode` is ignored.
+#[must_use]
+pub fn is_executable_entry(path: &Path, _mode: Option<u32>) -> bool {
+    is_executable(path)
+}
+
+/// Get the size of a file from its `metadata`.
+#[must_use]
+pub fn file_size(metadata: &Metadata) -> u64 {
+    metadata.file_size()
+}
+
+/// Set the size of `file` to `len`, so NTFS allocates the space up front.
+///
+/// # Errors
+///
+/// Will return `Err` if the file could not be resized.
+pub fn preallocate(file: &File, len: u64) -> io::Result<()> {
+    file.set_len(len)
+}
+
+/// Windows has no kernel copy between offsets of open files, so this never copies anything and returns `false`.
+///
+/// # Errors
+///
+/// Never returns `Err`, it is only fallible to match other platforms.
+pub fn copy_range(
+    _from: &File,
+    _from_offset: u64,
+    _to: &File,
+    _to_offset: u64,
+    _len: u64,
+    _copied: impl FnMut(u64),
+) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Write all of `buf` into `file` at `offset`.
+///
+/// # Errors
+///
+/// Will return `Err` if the write fails.
+pub fn write_all_at(file: &File, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        match file.seek_write(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                buf = &buf[written..];
+                offset += written as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Display `path` the way the user would type it, without the `\\?\` prefix.
+#[must_use]
+pub fn display_path(path: &Path) -> Cow<'_, str> {
+    dunce::simplified(path).to_string_lossy()
+}
+
+/// A command running `command` with `cmd`.
+#[must_use]
+pub fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    // cmd parses its command line itself, so it is passed on unquoted.
+    shell.arg("/C").raw_arg(command);
+    shell
+}
+
+/// Create a start menu shortcut called `app_name` to `executable` run with `args` using powershell,
+/// returning the path of the shortcut.
+///
+/// # Errors
+///
+/// Will return `Err` if `APPDATA` is not set, or powershell could not be run or failed.
+pub fn create_shortcut(
+    app_name: &str,
+    executable: &Path,
+    args: &[String],
+    working_dir: &Path,
+) -> Result<PathBuf, Error> {
+    let Ok(appdata) = std::env::var("APPDATA") else {
+        return Err(Error::Shortcut(
+            "Could not find environment variable APPDATA".to_string(),
+        ));
+    };
+    let start_menu = PathBuf::from(appdata).join(r"Microsoft\Windows\Start Menu\Programs");
+
+    let shortcut = start_menu.join(format!("{app_name}.{SHORTCUT_EXT}"));
+
+    // a single quoted powershell string only escapes `'`, by doubling it.
+    let arguments = join_args(args).replace('\'', "''");
+
+    // create a shortcut in powershell
+    let script = format!(
+        // do not need quotes around placeholder since PathBuf's Debug impl adds quotes
+        r"$shortcut = (New-Object -COMObject WScript.Shell).CreateShortcut({shortcut:?});
+            $shortcut.TargetPath = {executable:?};
+            $shortcut.Arguments = '{arguments}';
+            $shortcut.WorkingDirectory = {working_dir:?};
+            $shortcut.Save()",
+    );
+
+    let powershell = Command::new("powershell")
+        .args(["-c", &script])
+        .status()
+        .map_err(|err| Error::Shortcut(format!("Failed to run powershell: {err}")))?;
+
+    match powershell.code() {
+        Some(0) => {
+            println!("Successfully created shortcut to {executable:?}.");
+            Ok(shortcut)
+        }
+        Some(1) => Err(Error::Shortcut(
+            "Powershell encountered an uncaught error while creating the shortcut".to_string(),
+        )),
+        code => Err(Error::Shortcut(format!("Powershell exit code: {code:?}"))),
+    }
+}
+
+/// Join `args` into a command line, quoting the ones with spaces or quotes in them.
+fn join_args(args: &[String]) -> String {
+    let quoted: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+                return arg.clone();
+            }
+
+            format!("\"{}\"", arg.replace('"', "\\\""))
+        })
+        .collect();
+
+    quoted.join(" ")
+}
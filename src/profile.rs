@@ -0,0 +1,203 @@
+use std::{
+    env, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer};
+
+use crate::{extract::ExtractorKind, read_password, Error, Flatten};
+
+/// File name of the profile looked for next to the parts.
+pub const PROFILE_NAME: &str = "partsinstall.toml";
+
+/// Settings for installing one app, read from a [`PROFILE_NAME`] file.
+///
+/// Every setting is optional, and overrides what would otherwise be worked out from the app name.
+///
+/// ```toml
+/// folder = "My App"
+/// flatten = "repeated"
+/// extractor = "7z"
+/// password_file = "password.txt"
+/// post_install = ["./setup.sh --portable"]
+///
+/// [shortcut]
+/// executable = "myapp"
+/// name = "My App"
+/// args = ["--no-update"]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// Name of the folder the app is installed to in the destination, instead of the parsed app name.
+    pub folder: Option<String>,
+    /// How inner directories are flattened, by the name `--flatten` takes.
+    /// `false` turns flattening off, and `true` keeps the installer's strategy.
+    #[serde(deserialize_with = "deserialize_flatten")]
+    pub flatten: Option<Flatten>,
+    /// The inner directory to flatten, relative to the install folder, instead of the one named after the app.
+    pub flatten_dir: Option<PathBuf>,
+    /// The extractor to use, by the name `--extractor` takes.
+    #[serde(deserialize_with = "deserialize_extractor")]
+    pub extractor: Option<ExtractorKind>,
+    /// File to read the archive's password from, relative to the profile. The first line is used.
+    pub password_file: Option<PathBuf>,
+    /// Environment variable to read the archive's password from, if there is no `password_file`.
+    pub password_env: Option<String>,
+    pub shortcut: ShortcutProfile,
+    /// Commands run by the shell in the install folder once the app is installed.
+    pub post_install: Vec<String>,
+    /// The folder the profile was read from.
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+/// The `[shortcut]` table of a [`Profile`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShortcutProfile {
+    /// Whether a shortcut is created.
+    pub create: Option<bool>,
+    /// The executable the shortcut runs, relative to the install folder, instead of the one found.
+    pub executable: Option<PathBuf>,
+    /// Name of the shortcut, instead of the app name.
+    pub name: Option<String>,
+    /// Arguments the executable is run with.
+    pub args: Vec<String>,
+}
+
+impl Profile {
+    /// The path of the profile in `dir`, if there is one.
+    #[must_use]
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        let path = dir.join(PROFILE_NAME);
+        path.is_file().then_some(path)
+    }
+
+    /// Read the profile at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `path` could not be read, or is not a valid profile.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| Error::Profile(path.to_path_buf(), err))?;
+
+        let mut profile: Self = toml::from_str(&contents).map_err(|err| {
+            Error::Profile(
+                path.to_path_buf(),
+                io::Error::new(io::ErrorKind::InvalidData, err),
+            )
+        })?;
+        profile.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        profile.check().map_err(|msg| {
+            Error::Profile(
+                path.to_path_buf(),
+                io::Error::new(io::ErrorKind::InvalidData, msg),
+            )
+        })?;
+
+        Ok(profile)
+    }
+
+    /// Make sure every path in the profile stays inside the install folder, or the profile's folder for
+    /// `password_file`, since profiles come with the parts and cannot be trusted.
+    fn check(&self) -> Result<(), String> {
+        let is_name = |name: &str| {
+            let mut components = Path::new(name).components();
+            matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            )
+        };
+        let is_inside = |path: &Path| {
+            path.components().next().is_some()
+                && path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        };
+
+        if let Some(folder) = self.folder.as_deref().filter(|folder| !is_name(folder)) {
+            return Err(format!("folder {folder:?} is not a folder name"));
+        }
+        if let Some(name) = self.shortcut.name.as_deref().filter(|name| !is_name(name)) {
+            return Err(format!("shortcut name {name:?} is not a file name"));
+        }
+        if let Some(path) = [&self.flatten_dir, &self.shortcut.executable]
+            .into_iter()
+            .flatten()
+            .find(|path| !is_inside(path))
+        {
+            return Err(format!("{path:?} is not inside the install folder"));
+        }
+        if let Some(path) = self.password_file.as_ref().filter(|path| {
+            path.components().next().is_none()
+                || !path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+        }) {
+            return Err(format!(
+                "password file {path:?} is not inside the profile's folder"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The password from `password_file`, or `password_env` if it is set.
+    ///
+    /// Only use it for a profile the user chose, since either can point at any secret of theirs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the password file could not be read.
+    pub fn password(&self) -> Result<Option<String>, Error> {
+        if let Some(file) = &self.password_file {
+            return read_password(&self.dir.join(file)).map(Some);
+        }
+
+        Ok(self
+            .password_env
+            .as_ref()
+            .and_then(|var| env::var(var).ok()))
+    }
+
+    /// Returns true if the profile names where to read a password from.
+    #[must_use]
+    pub fn has_password(&self) -> bool {
+        self.password_file.is_some() || self.password_env.is_some()
+    }
+}
+
+/// Read a flatten strategy by the name `--flatten` takes, or whether to flatten at all.
+fn deserialize_flatten<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Flatten>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Setting {
+        Enabled(bool),
+        Strategy(String),
+    }
+
+    match Setting::deserialize(deserializer)? {
+        Setting::Enabled(true) => Ok(None),
+        Setting::Enabled(false) => Ok(Some(Flatten::Off)),
+        Setting::Strategy(name) => Flatten::from_str(&name, true)
+            .map(Some)
+            .map_err(|_| serde::de::Error::custom(format!("unknown flatten strategy {name:?}"))),
+    }
+}
+
+/// Read an extractor by the name `--extractor` takes.
+fn deserialize_extractor<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ExtractorKind>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+
+    ExtractorKind::from_str(&name, true)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("unknown extractor {name:?}")))
+}
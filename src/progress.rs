@@ -0,0 +1,206 @@
+use std::{
+    io::{self, IsTerminal, Read, Seek, SeekFrom},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use humansize::{format_size, DECIMAL};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// What a [`Progress`] counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    Bytes,
+    Percent,
+}
+
+/// Shows how far a long running step is, as a bar when stdout is a terminal,
+/// or as a log line every 10% when it is not.
+///
+/// Nothing is shown until the total is known, see [`Progress::start_bytes`] and [`Progress::start_percent`].
+#[derive(Clone)]
+pub struct Progress {
+    message: &'static str,
+    terminal: bool,
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    bar: Option<ProgressBar>,
+    unit: Unit,
+    total: u64,
+    done: u64,
+    /// Where counting started, so resumed steps do not count what was done before.
+    initial: u64,
+    /// The last tenth logged, when not drawing a bar.
+    logged: u64,
+    start: Instant,
+}
+
+impl Progress {
+    /// Progress of the step described by `message`, eg. `"Combining"`.
+    #[must_use]
+    pub fn new(message: &'static str) -> Self {
+        Self {
+            message,
+            terminal: io::stdout().is_terminal(),
+            state: Arc::new(Mutex::new(State {
+                bar: None,
+                unit: Unit::Bytes,
+                total: 0,
+                done: 0,
+                initial: 0,
+                logged: 0,
+                start: Instant::now(),
+            })),
+        }
+    }
+
+    /// Start showing progress through `total` bytes, of which `done` are already done.
+    pub fn start_bytes(&self, total: u64, done: u64) {
+        self.start(Unit::Bytes, total, done);
+    }
+
+    /// Start showing progress in percent.
+    pub fn start_percent(&self) {
+        self.start(Unit::Percent, 100, 0);
+    }
+
+    fn start(&self, unit: Unit, total: u64, done: u64) {
+        let mut state = self.state();
+
+        state.unit = unit;
+        state.total = total;
+        state.done = done;
+        state.initial = done;
+        state.logged = tenths(done, total);
+        state.start = Instant::now();
+
+        if self.terminal {
+            let template = match unit {
+                Unit::Bytes => {
+                    "{msg} [{wide_bar}] {decimal_bytes}/{decimal_total_bytes} ({decimal_bytes_per_sec}, ETA {eta})"
+                }
+                Unit::Percent => "{msg} [{wide_bar}] {pos}% (ETA {eta})",
+            };
+            let style = ProgressStyle::with_template(template)
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> ");
+
+            let bar = ProgressBar::with_draw_target(Some(total), ProgressDrawTarget::stdout())
+                .with_style(style)
+                .with_message(self.message)
+                .with_position(done);
+            bar.reset_eta();
+
+            state.bar = Some(bar);
+        }
+    }
+
+    /// Count `n` more units done.
+    pub fn inc(&self, n: u64) {
+        let mut state = self.state();
+        let done = state.done.saturating_add(n);
+        self.update(&mut state, done);
+    }
+
+    /// Set how many units are done.
+    pub fn set(&self, done: u64) {
+        let mut state = self.state();
+        self.update(&mut state, done);
+    }
+
+    fn update(&self, state: &mut State, done: u64) {
+        state.done = done.min(state.total);
+
+        if let Some(bar) = &state.bar {
+            bar.set_position(state.done);
+            return;
+        }
+
+        let tenths = tenths(state.done, state.total);
+        if state.total == 0 || tenths <= state.logged {
+            return;
+        }
+        state.logged = tenths;
+
+        match state.unit {
+            Unit::Percent => println!("{}: {}%", self.message, tenths * 10),
+            Unit::Bytes => {
+                let elapsed = state.start.elapsed().as_secs_f64();
+                let rate = state.done.saturating_sub(state.initial) as f64 / elapsed.max(0.001);
+                let eta = (state.total - state.done) as f64 / rate.max(1.0);
+
+                println!(
+                    "{}: {}% ({} of {}, {}/s, ETA {})",
+                    self.message,
+                    tenths * 10,
+                    format_size(state.done, DECIMAL),
+                    format_size(state.total, DECIMAL),
+                    format_size(rate as u64, DECIMAL),
+                    humantime::format_duration(Duration::from_secs(eta as u64))
+                );
+            }
+        }
+    }
+
+    /// Print `line` without breaking the bar.
+    pub fn println(&self, line: impl AsRef<str>) {
+        match &self.state().bar {
+            Some(bar) => bar.println(line),
+            None => println!("{}", line.as_ref()),
+        }
+    }
+
+    /// Stop showing progress, leaving the finished bar on the terminal.
+    pub fn finish(&self) {
+        if let Some(bar) = self.state().bar.take() {
+            bar.finish();
+        }
+    }
+
+    /// Wrap `inner`, counting the bytes read from it as done.
+    #[must_use]
+    pub fn reader<R>(&self, inner: R) -> ProgressReader<R> {
+        ProgressReader {
+            inner,
+            progress: self.clone(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // the state is always left consistent, so a panic while holding it does not matter.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// How many whole tenths of `total` are `done`.
+fn tenths(done: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+
+    // widen so large totals cannot overflow.
+    (u128::from(done) * 10 / u128::from(total)) as u64
+}
+
+/// Counts the bytes read through it in a [`Progress`].
+pub struct ProgressReader<R> {
+    inner: R,
+    progress: Progress,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.inc(read as u64);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for ProgressReader<R> {
+    // archives are read mostly in order after their index, so bytes read are counted instead of the position.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
@@ -1,20 +1,42 @@
 use std::{
     borrow::Cow,
-    fs::{self, File},
-    io,
-    os::windows::fs::MetadataExt,
+    collections::BTreeSet,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    num::NonZeroUsize,
+    panic,
     path::{Path, PathBuf},
-    process::{exit, Command},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
-use glob::glob;
 use humansize::{format_size, DECIMAL};
-use partsinstall::{
-    compare_numeric_extension, name_has_keywords, print_flush, prompt, prompt_user_for_path,
-    prompt_user_for_usize, PathExt,
+
+use crate::{
+    checksum::Checksums,
+    compare_numeric_extension,
+    extract::{self, Answers, ExtractError, ExtractorKind},
+    interact::Interactor,
+    name_has_keywords, numeric_extension,
+    parts::PartsReader,
+    platform, print_flush,
+    profile::ShortcutProfile,
+    progress::Progress,
+    volumes::{volume_stem, VolumeKind, VolumeSet},
+    Error, Flatten, PathExt,
 };
 
+/// How many parts are combined at once.
+/// Combining is mostly limited by the disk, so more threads only make the parts compete for it.
+const COMBINE_THREADS: usize = 4;
+
+/// Size of the chunks parts are copied in when the kernel cannot copy them itself.
+const COPY_BUF_SIZE: usize = 1024 * 1024;
+
 /// Parse the app name from `name`.
 #[must_use]
 pub fn parse_app_name(name: &Path) -> Option<Cow<'_, str>> {
@@ -34,6 +56,11 @@ pub fn parse_app_name(name: &Path) -> Option<Cow<'_, str>> {
 
     // we now know `name` exists and is a file (not a dir).
 
+    // remove volume suffix. eg. app.part1.rar and app.r00 would become app
+    if let Some(stem) = volume_stem(&name_str) {
+        return Some(Cow::Owned(stem.to_string()));
+    }
+
     // remove numeric extension. eg. app.7z.001 would become app.7z
     let name = if name.is_numeric() {
         name.lossy_file_stem()?
@@ -56,304 +83,1256 @@ pub fn parse_app_name(name: &Path) -> Option<Cow<'_, str>> {
     Some(Cow::Owned(file_name.into_owned()))
 }
 
-/// Find the final output name, combining `files` if needed, returning the final name and time taken to combine (if any). 
-pub fn find_final_name<'a>(
+/// Find the names of the apps with parts or archives in `dir`, sorted and without duplicates.
+///
+/// Every file that is numbered, a volume of a multi-volume archive, or an archive is parsed with [`parse_app_name`],
+/// so the parts of one app and the archive combined from them are found as one app.
+///
+/// # Errors
+///
+/// Will return `Err` if `dir` could not be read.
+pub fn find_app_names(dir: &Path) -> Result<Vec<String>, Error> {
+    let entries = dir
+        .read_dir()
+        .map_err(|err| Error::WorkingDir(dir.to_path_buf(), err))?;
+
+    let names: BTreeSet<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && (path.is_numeric()
+                    || path.is_archive()
+                    || path
+                        .lossy_file_name()
+                        .is_some_and(|name| volume_stem(&name).is_some()))
+        })
+        .filter_map(|path| parse_app_name(&path).map(Cow::into_owned))
+        .collect();
+
+    Ok(names.into_iter().collect())
+}
+
+/// Find the final archive path, combining `volume_set` into `output_dir` if needed,
+/// returning the final path and time taken to combine (if any).
+///
+/// Multi-volume sets are never combined, since the extractor reads the other volumes itself.
+/// Parts with a checksum in `checksums` are verified while combining, or before extracting if they are not combined.
+///
+/// # Errors
+///
+/// Will return `Err` if the user cancels, a part does not match its checksum, or combining fails.
+pub fn find_final_name(
     app_name: &str,
-    files: &'a mut [PathBuf],
-    no_interaction: bool,
-) -> (Cow<'a, str>, Duration) {
-    if files.len() == 1 {
-        if no_interaction {
-            (files[0].to_string_lossy(), Duration::ZERO)
-        } else {
-            print_flush!("Only 1 file found, extract {:?}? (y/n): ", files[0]);
+    volume_set: &mut VolumeSet,
+    output_dir: &Path,
+    checksums: &Checksums,
+    interactor: &dyn Interactor,
+) -> Result<(PathBuf, Duration), Error> {
+    let files = &mut volume_set.volumes;
 
-            // true
-            if prompt().to_lowercase() != "y" {
-                exit(1)
-            }
+    if volume_set.kind.is_multi_volume() {
+        println!(
+            "Found {} volume(s) of a {} archive, extracting from {:?}",
+            files.len(),
+            volume_set.kind,
+            files[0]
+        );
+        checksums.verify_all(files)?;
 
-            (files[0].to_string_lossy(), Duration::ZERO)
+        Ok((files[0].clone(), Duration::ZERO))
+    } else if files.len() == 1 {
+        if !interactor.confirm(&format!("Only 1 file found, extract {:?}?", files[0]))? {
+            return Err(Error::Cancelled);
         }
+        checksums.verify_all(files)?;
+
+        Ok((files[0].clone(), Duration::ZERO))
     } else {
         let combine_start = Instant::now();
 
-        let final_ext = files
-            .iter()
-            .find_map(|p| p.file_name())
-            .expect("No file names could be found")
-            .to_string_lossy();
-        let final_ext = final_ext
-            .split('.')
-            // skip the file stem
-            .skip(1)
-            // find extension which is not a number
-            // eg. from file.7z.001, we want 7z, ignoring 001.
-            .find(|part| part.parse::<u32>().is_err())
-            .expect("Could not determine output file extension");
-
-        let final_name = format!("{app_name}.{final_ext}");
-
-        println!("Combining to {final_name}");
-
-        let combine_time = combine_files(files, &final_name, combine_start, no_interaction)
+        let final_name = combined_name(app_name, files, output_dir)?;
+
+        // an archive combined by an earlier run is found with the parts, but is not one of them.
+        files.retain(|file| file.file_name() != final_name.file_name());
+        check_sequence(files)?;
+
+        println!("Combining to {final_name:?}");
+
+        let combine_time = combine_files(files, &final_name, checksums, combine_start, interactor)?
             .unwrap_or(Duration::ZERO);
 
-        (Cow::Owned(final_name), combine_time)
+        Ok((final_name, combine_time))
+    }
+}
+
+/// The path in `output_dir` of the archive the split `files` of `app_name` are combined into.
+///
+/// # Errors
+///
+/// Will return `Err` if no file names or extensions could be found.
+pub fn combined_name(
+    app_name: &str,
+    files: &[PathBuf],
+    output_dir: &Path,
+) -> Result<PathBuf, Error> {
+    Ok(output_dir.join(format!("{app_name}.{}", find_final_extension(files)?)))
+}
+
+/// Find the extension of the archive `files` combine into.
+///
+/// # Errors
+///
+/// Will return `Err` if no file names or extensions could be found.
+pub fn find_final_extension(files: &[PathBuf]) -> Result<String, Error> {
+    let file_name = files
+        .iter()
+        .find_map(|p| p.file_name())
+        .ok_or(Error::UnknownExtension)?
+        .to_string_lossy();
+
+    file_name
+        .split('.')
+        // skip the file stem
+        .skip(1)
+        // find extension which is not a number
+        // eg. from file.7z.001, we want 7z, ignoring 001.
+        .find(|part| part.parse::<u32>().is_err())
+        .map(ToString::to_string)
+        .ok_or(Error::UnknownExtension)
+}
+
+/// Sort `files` by their numeric extensions.
+pub fn sort_parts(files: &mut [PathBuf]) {
+    // glob sorts alphanumerically, meaning it will sort correctly until a number is larger than 10.
+    // eg. 01, 11, 02, 021, 03 will be how glob sorts numbers larger than 10.
+    if files.len() > 10 {
+        files.sort_by_key(|file| numeric_extension(file));
     }
 }
 
-/// Combine `files` into one file named `final_name`, prompting user and exiting if needed.
-#[allow(
-    clippy::missing_panics_doc,
-    reason = "We want to panic/exit if something fails here."
-)]
+/// Check `files` are numbered contiguously from 1 (or 0) with the same zero-padding,
+/// sorting them by number.
+///
+/// # Errors
+///
+/// Will return `Err` if there are no files, a file is not numbered, the numbers are padded differently,
+/// a number is used more than once, or any numbers are missing.
+pub fn check_sequence(files: &mut [PathBuf]) -> Result<(), Error> {
+    if files.is_empty() {
+        return Err(Error::MissingParts(vec![1]));
+    }
+
+    if let Some(file) = files.iter().find(|file| numeric_extension(file).is_none()) {
+        return Err(Error::NotNumbered(file.clone()));
+    }
+
+    files.sort_by(|a, b| compare_numeric_extension(a, b));
+
+    // the numbers of parts are padded to the width of the first part's,
+    // and only numbers too large for that width are longer.
+    let width = |file: &PathBuf| file.lossy_extension().map_or(0, |ext| ext.len());
+    let padded = width(&files[0]);
+    let mixed: Vec<PathBuf> = files
+        .iter()
+        .filter(|file| {
+            let width = width(file);
+            width < padded
+                || (width > padded
+                    && file
+                        .lossy_extension()
+                        .is_some_and(|ext| ext.starts_with('0')))
+        })
+        .cloned()
+        .collect();
+    if !mixed.is_empty() {
+        return Err(Error::MixedPadding(mixed));
+    }
+
+    let numbers: Vec<u32> = files
+        .iter()
+        .filter_map(|file| numeric_extension(file))
+        .collect();
+
+    let duplicates: Vec<PathBuf> = files
+        .iter()
+        .zip(&numbers)
+        .filter(|(_, n)| numbers.iter().filter(|other| other == n).count() > 1)
+        .map(|(file, _)| file.clone())
+        .collect();
+    if !duplicates.is_empty() {
+        return Err(Error::DuplicateParts(duplicates));
+    }
+
+    let first = numbers[0].min(1);
+    let last = numbers[numbers.len() - 1];
+    let missing: Vec<u32> = (first..=last)
+        .filter(|n| numbers.binary_search(n).is_err())
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::MissingParts(missing));
+    }
+
+    Ok(())
+}
+
+/// Combine `files` into one file at `output`, prompting user if needed.
+///
+/// `output` is preallocated to the size of the parts combined, and the parts are written into it at their offsets
+/// by up to [`COMBINE_THREADS`] threads, copied by the kernel where it can.
+/// Each part's time is printed once it is written.
+///
+/// If an earlier combine into `output` was interrupted, the user is offered to resume it,
+/// only writing the parts (or rest of the part) it did not finish.
+/// Returns `None` if `output` is already fully combined and the user chose to extract it.
+/// Parts with a checksum in `checksums` are verified as they are copied, and `output` is removed if one does not match.
+///
+/// # Errors
+///
+/// Will return `Err` if the user cancels, any part could not be copied, or a part does not match its checksum.
 pub fn combine_files(
     files: &mut [PathBuf],
-    output_name: &str,
+    output: &Path,
+    checksums: &Checksums,
     start: Instant,
-    no_interaction: bool,
-) -> Option<Duration> {
-    let final_file = File::create_new(output_name);
+    interactor: &dyn Interactor,
+) -> Result<Option<Duration>, Error> {
+    sort_parts(files);
 
-    if let Ok(mut final_file) = final_file {
-        let files_len = files.len();
+    let sizes = files
+        .iter()
+        .map(|file| {
+            fs::metadata(file)
+                .map(|metadata| platform::file_size(&metadata))
+                .map_err(|err| Error::Combine(file.clone(), err))
+        })
+        .collect::<Result<Vec<u64>, Error>>()?;
+    let total = sizes.iter().sum();
 
-        // glob sorts alphanumerically, meaning it will sort correctly until a number is larger than 10.
-        // eg. 01, 11, 02, 021, 03 will be how glob sorts numbers larger than 10.
-        if files.len() > 10 {
-            files.sort_by(|a, b| compare_numeric_extension(a, b));
+    let (final_file, combined) = match File::create_new(output) {
+        Ok(final_file) => (final_file, vec![0; files.len()]),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            match open_combined(output, files, &sizes, interactor)? {
+                Some(opened) => opened,
+                None => {
+                    // a stale or corrupt archive is never extracted, and combined again by the next run.
+                    if let Err(err) = checksums.verify_combined(output, files, &sizes) {
+                        if matches!(err, Error::Checksum(..)) {
+                            let _ = fs::remove_file(output);
+                        }
+                        return Err(err);
+                    }
+                    return Ok(None);
+                }
+            }
         }
+        Err(err) => return Err(Error::Combine(output.to_path_buf(), err)),
+    };
 
-        for (n, file) in files.iter().enumerate() {
-            if let Ok(metadata) = fs::metadata(file) {
-                let size = format_size(metadata.file_size(), DECIMAL);
-                println!("{}/{files_len}: combining {file:?} ({size})", n + 1);
-            } else {
-                println!("{}/{files_len}: combining {file:?}", n + 1);
-            }
+    let written: Vec<&PathBuf> = files
+        .iter()
+        .zip(&sizes)
+        .zip(&combined)
+        .filter(|((_, size), combined)| size == combined)
+        .map(|((file, _), _)| file)
+        .collect();
+    // the journal is written before the file gets its full size,
+    // so a full size file without one is never taken as fully combined.
+    let journal = match Journal::create(output, &written) {
+        Ok(journal) => journal,
+        Err(err) => {
+            drop(final_file);
+            let _ = fs::remove_file(output);
+            return Err(err);
+        }
+    };
+
+    platform::preallocate(&final_file, total)
+        .map_err(|err| Error::Combine(output.to_path_buf(), err))?;
 
-            // do not use BufReader here since we expect large files to be combined.
-            // (benched and saw larger files took longer to combine with the use of BufReader than not.)
-            let mut file = File::open(file).expect("File could not be opened");
-            io::copy(&mut file, &mut final_file).expect("Failed to copy files");
+    let resumed = combined.iter().sum();
+    if resumed > 0 {
+        println!(
+            "Resuming with {} of {} already combined",
+            format_size(resumed, DECIMAL),
+            format_size(total, DECIMAL)
+        );
+    }
+
+    // parts combined before resuming are read again only to verify them.
+    let written: Vec<PathBuf> = written.into_iter().cloned().collect();
+    let result = checksums.verify_all(&written).and_then(|()| {
+        let progress = Progress::new("Combining");
+        progress.start_bytes(total, resumed);
+
+        let result = combine_parts(
+            files,
+            &sizes,
+            &combined,
+            &final_file,
+            &journal,
+            checksums,
+            &progress,
+        );
+
+        progress.finish();
+        result
+    });
+
+    match result {
+        Ok(()) => journal.remove()?,
+        Err(err) => {
+            if matches!(err, Error::Checksum(..)) {
+                // never leave a corrupt archive behind to be extracted by the next run.
+                drop(final_file);
+                let _ = fs::remove_file(output);
+                let _ = journal.remove();
+            }
+            return Err(err);
         }
+    }
 
-        Some(start.elapsed())
-    } else {
-        let err = final_file.expect_err("File must be Err here.");
+    Ok(Some(start.elapsed()))
+}
 
-        if matches!(err.kind(), io::ErrorKind::AlreadyExists) {
-            // skip prompt
-            if no_interaction {
-                println!("File \"{output_name}\" already exists, extracting.");
-                None
-            } else {
-                print_flush!("File \"{output_name}\" already exists, extract it? (y/n): ");
+/// Open the existing `output` of an earlier combine of `files` to continue writing to it,
+/// returning it and how many bytes of each part were already combined, or `None` if it is complete.
+fn open_combined(
+    output: &Path,
+    files: &[PathBuf],
+    sizes: &[u64],
+    interactor: &dyn Interactor,
+) -> Result<Option<(File, Vec<u64>)>, Error> {
+    let total: u64 = sizes.iter().sum();
+    let combined = fs::metadata(output)
+        .map(|metadata| platform::file_size(&metadata))
+        .map_err(|err| Error::Combine(output.to_path_buf(), err))?;
 
-                if prompt().to_lowercase() != "y" {
-                    exit(1);
-                }
+    let open = |truncate: bool| {
+        OpenOptions::new()
+            .write(true)
+            .truncate(truncate)
+            .open(output)
+            .map_err(|err| Error::Combine(output.to_path_buf(), err))
+    };
+
+    match Journal::read(output)? {
+        // the output of an interrupted combine already has its full size, only the journal knows what was written.
+        Some(written) if combined == total => {
+            let combined: Vec<u64> = files
+                .iter()
+                .zip(sizes)
+                .map(|(file, &size)| {
+                    let is_written = file
+                        .lossy_file_name()
+                        .is_some_and(|name| written.iter().any(|written| *written == name));
+                    if is_written {
+                        size
+                    } else {
+                        0
+                    }
+                })
+                .collect();
 
-                None
+            if interactor.confirm(&format!(
+                "File {output:?} is partly combined ({} of {}), resume combining it?",
+                format_size(combined.iter().sum::<u64>(), DECIMAL),
+                format_size(total, DECIMAL)
+            ))? {
+                return Ok(Some((open(false)?, combined)));
             }
-        } else {
-            panic!("File {output_name} was unable to be created: {err:?}")
         }
+        Some(_) => println!("File {output:?} is partly combined, but has changed since."),
+        // only reuse a file with exactly the size of the parts combined, so a truncated archive is never extracted.
+        None if combined == total => {
+            if !interactor.confirm(&format!(
+                "File {output:?} already exists and is fully combined, extract it?"
+            ))? {
+                return Err(Error::Cancelled);
+            }
+
+            return Ok(None);
+        }
+        // combined one part after another, so everything up to its size was written.
+        None if combined < total => {
+            if interactor.confirm(&format!(
+                "File {output:?} is partly combined ({} of {}), resume combining it?",
+                format_size(combined, DECIMAL),
+                format_size(total, DECIMAL)
+            ))? {
+                let mut left = combined;
+                let combined = sizes
+                    .iter()
+                    .map(|&size| {
+                        let written = left.min(size);
+                        left -= written;
+                        written
+                    })
+                    .collect();
+
+                return Ok(Some((open(false)?, combined)));
+            }
+        }
+        None => println!(
+            "File {output:?} is larger than the parts combined ({} > {}).",
+            format_size(combined, DECIMAL),
+            format_size(total, DECIMAL)
+        ),
     }
+
+    if !interactor.confirm(&format!("Combine {output:?} again from the start?"))? {
+        return Err(Error::Cancelled);
+    }
+
+    Ok(Some((open(true)?, vec![0; files.len()])))
 }
 
-/// Create destination path, handling errors and giving prompts as needed.
+/// Lists the parts fully written into a combined file, which has its full size from the start,
+/// so an interrupted combine can be resumed.
 ///
-/// # Panics
+/// Kept next to the combined file as a hidden `.{name}.combining`, so it is never found with the parts.
+struct Journal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    fn path(output: &Path) -> PathBuf {
+        let name = output.lossy_file_name().unwrap_or_default();
+        output.with_file_name(format!(".{name}.combining"))
+    }
+
+    /// Start the journal of combining into `output`, with the parts already `written`.
+    fn create(output: &Path, written: &[&PathBuf]) -> Result<Self, Error> {
+        let path = Self::path(output);
+        let mut file = File::create(&path).map_err(|err| Error::Combine(path.clone(), err))?;
+
+        for part in written {
+            writeln!(file, "{}", part.lossy_file_name().unwrap_or_default())
+                .map_err(|err| Error::Combine(path.clone(), err))?;
+        }
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Read the names of the parts written into `output`, `None` if it has no journal.
+    fn read(output: &Path) -> Result<Option<Vec<String>>, Error> {
+        let path = Self::path(output);
+
+        match fs::read_to_string(&path) {
+            Ok(journal) => Ok(Some(journal.lines().map(ToString::to_string).collect())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::Combine(path, err)),
+        }
+    }
+
+    /// Record that `part` was fully written.
+    fn record(&self, part: &Path) -> Result<(), Error> {
+        // only one line is written at a time, so a panic while holding the lock cannot leave it half written.
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+
+        writeln!(file, "{}", part.lossy_file_name().unwrap_or_default())
+            .map_err(|err| Error::Combine(self.path.clone(), err))
+    }
+
+    /// Remove the journal, once combining is finished.
+    fn remove(self) -> Result<(), Error> {
+        drop(self.file);
+        fs::remove_file(&self.path).map_err(|err| Error::Combine(self.path, err))
+    }
+}
+
+/// A part to write into the combined file.
+struct Part<'a> {
+    file: &'a Path,
+    /// eg. `2/5`.
+    count: String,
+    size: u64,
+    /// How much of the part was already combined.
+    combined: u64,
+    /// Where the part starts in the combined file.
+    position: u64,
+}
+
+/// Write the rest of each of `files` into `final_file`, using up to [`COMBINE_THREADS`] threads,
+/// recording each part in `journal` once it is written.
+fn combine_parts(
+    files: &[PathBuf],
+    sizes: &[u64],
+    combined: &[u64],
+    final_file: &File,
+    journal: &Journal,
+    checksums: &Checksums,
+    progress: &Progress,
+) -> Result<(), Error> {
+    let mut position = 0;
+    let mut parts = Vec::with_capacity(files.len());
+    for (n, file) in files.iter().enumerate() {
+        if combined[n] < sizes[n] {
+            parts.push(Part {
+                file,
+                count: format!("{}/{}", n + 1, files.len()),
+                size: sizes[n],
+                combined: combined[n],
+                position,
+            });
+        }
+        position += sizes[n];
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(COMBINE_THREADS)
+        .min(parts.len());
+
+    let mut errors: Vec<Error> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    // stop taking parts once one fails, they are combined when resuming.
+                    while !failed.load(Ordering::Relaxed) {
+                        let Some(part) = parts.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                            break;
+                        };
+
+                        let result = combine_part(part, final_file, checksums, progress)
+                            .and_then(|()| journal.record(part.file));
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                            return result;
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .filter_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| panic::resume_unwind(panic))
+                    .err()
+            })
+            .collect()
+    });
+
+    // a part not matching its checksum matters more than the errors of other parts.
+    errors.sort_by_key(|err| !matches!(err, Error::Checksum(..)));
+    errors.into_iter().next().map_or(Ok(()), Err)
+}
+
+/// Write the rest of `part` into `final_file` at its position, verifying it if it has a checksum.
+fn combine_part(
+    part: &Part,
+    final_file: &File,
+    checksums: &Checksums,
+    progress: &Progress,
+) -> Result<(), Error> {
+    let start = Instant::now();
+    let combine_err = |err| Error::Combine(part.file.to_path_buf(), err);
+
+    let checksum = checksums.get(part.file);
+    let file = File::open(part.file).map_err(combine_err)?;
+    let len = part.size - part.combined;
+    let position = part.position + part.combined;
+
+    if checksum.is_some() {
+        let mut file = checksums.reader(part.file, file);
+
+        // the part has to be hashed from the start, even if only the rest is combined.
+        io::copy(&mut (&mut file).take(part.combined), &mut io::sink()).map_err(combine_err)?;
+        copy_at(&mut progress.reader(&mut file), final_file, position).map_err(combine_err)?;
+
+        file.finish()?;
+    } else if !platform::copy_range(&file, part.combined, final_file, position, len, |n| {
+        progress.inc(n);
+    })
+    .map_err(combine_err)?
+    {
+        let mut file = file;
+        file.seek(SeekFrom::Start(part.combined))
+            .map_err(combine_err)?;
+        copy_at(&mut progress.reader(file), final_file, position).map_err(combine_err)?;
+    }
+
+    let elapsed = start.elapsed();
+    let verified = checksum.map_or(String::new(), |checksum| {
+        format!(", verified {}", checksum.algorithm)
+    });
+    progress.println(format!(
+        "{}: combined {:?} ({} in {elapsed:?}, {}/s{verified})",
+        part.count,
+        part.file,
+        format_size(len, DECIMAL),
+        format_size(
+            (len as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE)) as u64,
+            DECIMAL
+        )
+    ));
+
+    Ok(())
+}
+
+/// Copy everything read from `reader` into `file`, starting at `position`.
+fn copy_at(reader: &mut impl Read, file: &File, mut position: u64) -> io::Result<()> {
+    // several parts are written into `file` at once, so its cursor cannot be used.
+    let mut buf = vec![0; COPY_BUF_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        platform::write_all_at(file, &buf[..read], position)?;
+        position += read as u64;
+    }
+}
+
+/// Create the [`Staging`] folder the app is installed in before it is moved to `destination`,
+/// handling errors and giving prompts as needed.
+///
+/// # Errors
 ///
-/// Will panic if destination folder already exists is not readable.
-pub fn create_destination(destination: &Path, no_interaction: bool) {
-    let Err(err) = fs::create_dir(destination) else {
-        return;
+/// Will return `Err` if the destination folder already exists and is not readable,
+/// the staging folder could not be created, or the user cancels.
+pub fn create_destination(
+    destination: &Path,
+    interactor: &dyn Interactor,
+) -> Result<Staging, Error> {
+    let merge = match destination
+        .read_dir()
+        .map(|mut files| files.next().is_none())
+    {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+        Err(err) => return Err(Error::Destination(destination.to_path_buf(), err)),
+        Ok(true) => {
+            println!("Destination folder already exists but is empty, continuing.");
+            false
+        }
+        Ok(false) => {
+            if !interactor
+                .confirm("Destination folder already exists and is not empty. Continue anyway?")?
+            {
+                return Err(Error::Cancelled);
+            }
+            true
+        }
     };
 
-    match err.kind() {
-        io::ErrorKind::AlreadyExists => {
-            let Ok(files) = destination.read_dir() else {
-                panic!("Destination folder already exists and could not be read.")
-            };
+    Staging::create(destination, merge)
+}
 
-            if no_interaction {
-                println!("Destination folder already exists and is not empty, continuing because of -y flag.");
-            } else if files.collect::<Vec<_>>().is_empty() {
-                println!("Destination folder already exists but is empty, continuing.");
-            } else {
-                print_flush!(
-                    "Destination folder already exists and is not empty. Continue anyway? (y/n): "
-                );
+/// Staging folders of installs in progress, see [`remove_staging`].
+static STAGING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
 
-                if prompt().to_lowercase() != "y" {
-                    exit(1)
-                }
+/// A folder next to an app's destination that the app is extracted and flattened in,
+/// then moved into place by [`Staging::commit`].
+///
+/// The folder is removed if it is dropped before being committed, so a failed install
+/// never leaves a half-written destination behind.
+#[derive(Debug)]
+pub struct Staging {
+    dir: PathBuf,
+    destination: PathBuf,
+    /// Whether the destination already has files, which the staged ones are moved in among.
+    merge: bool,
+}
+
+impl Staging {
+    fn create(destination: &Path, merge: bool) -> Result<Self, Error> {
+        let mut name = std::ffi::OsString::from(".");
+        name.push(destination.file_name().unwrap_or_default());
+        name.push(".partsinstall");
+        let dir = destination.with_file_name(name);
+
+        // left behind by an install that was killed.
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|err| Error::Destination(dir.clone(), err))?;
+        }
+        fs::create_dir(&dir).map_err(|err| Error::Destination(dir.clone(), err))?;
+
+        STAGING
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(dir.clone());
+
+        Ok(Self {
+            dir,
+            destination: destination.to_path_buf(),
+            merge,
+        })
+    }
+
+    /// The folder to install into.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Move the staged app to its destination, at once by renaming the staging folder
+    /// unless the destination already had files.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the staged files could not be moved.
+    pub fn commit(self) -> Result<(), Error> {
+        println!("Moving installed files to {:?}", self.destination);
+
+        if self.merge {
+            return move_into(&self.dir, &self.destination);
+        }
+
+        let destination_error = |err| Error::Destination(self.destination.clone(), err);
+
+        // renaming over a folder only works on unix, and only if it is empty.
+        match fs::remove_dir(&self.destination) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(destination_error(err))
             }
+            _ => (),
         }
-        err => panic!("Could not create destination folder: {err}"),
+        fs::rename(&self.dir, &self.destination).map_err(destination_error)
     }
 }
 
-/// Move all contents of a directory called `name` in `dir` to `dir`.
-/// eg. `App/App/files -> App/files`
-#[allow(
-    clippy::missing_panics_doc,
-    reason = "The expect_err() used will never panic since it is in a let Ok() else block."
-)]
-pub fn flatten_dir(name: impl AsRef<str>, dir: &Path) {
-    let Ok(dir_entries) = dir.read_dir() else {
-        println!("Directory was not readable, not flattening.");
-        return;
-    };
+impl Drop for Staging {
+    fn drop(&mut self) {
+        // already gone once committed.
+        if self.dir.exists() {
+            if let Err(err) = fs::remove_dir_all(&self.dir) {
+                println!("Could not remove staging folder {:?}: {err}", self.dir);
+            }
+        }
 
-    let name = name.as_ref();
+        STAGING
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|dir| dir != &self.dir);
+    }
+}
 
-    let inner_dir = dir_entries
-        .filter_map(Result::ok)
-        .find(|d| d.path().is_dir() && name_has_keywords(name.split(' '), &d.path()));
+/// Remove the staging folders of every install in progress,
+/// for when the process is about to be ended, eg. by Ctrl-C.
+pub fn remove_staging() {
+    let dirs = STAGING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    for dir in dirs.iter() {
+        // the extractor can still be writing to it, so removing is retried.
+        for _ in 0..5 {
+            if fs::remove_dir_all(dir).is_ok() || !dir.exists() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Move everything in `from` to `to`, merging folders and replacing files that are in both.
+fn move_into(from: &Path, to: &Path) -> Result<(), Error> {
+    let entries = from
+        .read_dir()
+        .map_err(|err| Error::Destination(from.to_path_buf(), err))?;
 
-    let Some(inner_dir) = inner_dir else {
-        println!("No inner directory to flatten.");
-        return;
+    for entry in entries {
+        let entry = entry.map_err(|err| Error::Destination(from.to_path_buf(), err))?;
+        let path = entry.path();
+        let moved_path = to.join(entry.file_name());
+
+        let is_dir = |path: &Path| fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir());
+        if is_dir(&path) && is_dir(&moved_path) {
+            move_into(&path, &moved_path)?;
+            continue;
+        }
+
+        if is_dir(&moved_path) {
+            fs::remove_dir_all(&moved_path)
+        } else {
+            fs::remove_file(&moved_path)
+        }
+        .or_else(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                Ok(())
+            } else {
+                Err(err)
+            }
+        })
+        .and_then(|()| fs::rename(&path, &moved_path))
+        .map_err(|err| Error::Destination(moved_path.clone(), err))?;
+    }
+
+    Ok(())
+}
+
+/// Extract `archive` into `destination` with the extractor of `kind`,
+/// or the first available extractor that supports `archive` if `kind` is `None`.
+///
+/// `archive` is the first volume of a set of `volumes`, decrypted with `password` if it is encrypted.
+/// If the password is wrong or missing, the user is asked for another one until it is right.
+///
+/// # Errors
+///
+/// Will return `Err` if no extractor supports `archive`, extracting fails,
+/// or the password is wrong and the user gives up.
+pub fn extract(
+    archive: &Path,
+    destination: &Path,
+    kind: Option<ExtractorKind>,
+    volumes: VolumeKind,
+    password: Option<&str>,
+    interactor: &dyn Interactor,
+) -> Result<(), Error> {
+    let extractor = match kind {
+        Some(kind) => kind.extractor(),
+        None => extract::probe(archive, volumes)
+            .ok_or_else(|| Error::NoExtractor(archive.to_path_buf()))?,
     };
 
-    let Ok(inner_entries) = inner_dir.path().read_dir() else {
-        println!("Could not read inner directory {:?}", inner_dir.path());
-        return;
+    let mut answers = if interactor.is_interactive() {
+        Answers::Ask
+    } else if interactor.assumes_yes() {
+        Answers::Yes
+    } else {
+        Answers::No
     };
+    match answers {
+        Answers::Ask => println!("\nExtracting with {}", extractor.name()),
+        Answers::Yes => println!(
+            "\nExtracting with {}, assuming yes on all prompts",
+            extractor.name()
+        ),
+        Answers::No => println!(
+            "\nExtracting with {}, answering no on all prompts",
+            extractor.name()
+        ),
+    }
 
-    let mut flattened = 0;
+    let mut password = password.map(ToString::to_string);
+    loop {
+        let result = extractor.extract(
+            archive,
+            destination,
+            answers,
+            password.as_deref(),
+            &Progress::new("Extracting"),
+        );
 
-    for inner_entry in inner_entries {
-        let Ok(inner_entry) = inner_entry else {
-            println!(
-                "Skipped flattening inner file/folder, got error {}.",
-                inner_entry
-                    .expect_err(".err() must work in a let Ok() else block, how did we get here?")
-            );
-            continue;
+        let Err(ExtractError::WrongPassword(name)) = result else {
+            result?;
+            break;
         };
 
-        let inner_entry_path = inner_entry.path();
-        let moved_path = dir.join(inner_entry.file_name());
+        println!();
+        let question = if password.is_some() {
+            format!("Wrong password for {archive:?}, try another")
+        } else {
+            format!("{archive:?} is encrypted, enter its password")
+        };
+        let Some(answer) = interactor.ask_password(&question)? else {
+            return Err(ExtractError::WrongPassword(name).into());
+        };
 
-        if let Err(err) = fs::rename(&inner_entry_path, &moved_path) {
-            println!(
-                "Got error {} while trying to move {:?} to {:?}\n",
-                err.kind(),
-                inner_entry_path,
-                moved_path
-            );
-            continue;
+        password = Some(answer);
+        // files extracted before the password was found wrong are overwritten without asking.
+        answers = Answers::Yes;
+    }
+
+    println!();
+    Ok(())
+}
+
+/// Extract `files` as one archive by streaming them into the extractor,
+/// without writing the combined archive to disk, decrypting it with `password` if it is encrypted.
+///
+/// The parts can only be streamed once, so a wrong password is not asked for again.
+///
+/// # Errors
+///
+/// Will return `Err` if no extractor can stream the archive, any part could not be read, or extracting fails.
+pub fn extract_parts(
+    files: &mut [PathBuf],
+    destination: &Path,
+    kind: Option<ExtractorKind>,
+    password: Option<&str>,
+) -> Result<(), Error> {
+    sort_parts(files);
+
+    let format = find_final_extension(files)?.to_lowercase();
+
+    let extractor = match kind {
+        Some(kind) => kind.extractor(),
+        None => extract::probe_stream(&format)
+            .ok_or_else(|| Error::NoStreamExtractor(format.clone()))?,
+    };
+
+    let reader = PartsReader::open(files)
+        .map_err(|err| Error::Combine(files.first().cloned().unwrap_or_default(), err))?;
+
+    println!(
+        "\nStreaming {} part(s) ({}) into {}",
+        files.len(),
+        format_size(reader.len(), DECIMAL),
+        extractor.name()
+    );
+
+    let progress = Progress::new("Extracting");
+    progress.start_bytes(reader.len(), 0);
+
+    let result = extractor.extract_stream(
+        Box::new(BufReader::new(progress.reader(reader))),
+        &format,
+        destination,
+        password,
+    );
+    progress.finish();
+    result?;
+
+    println!();
+    Ok(())
+}
+
+/// Move the contents of inner directories of `dir`, the install folder of the app `name`, to `dir`
+/// as `strategy` says.
+///
+/// [`Flatten::Keyword`] flattens a directory named after the app, eg. `App/App/files -> App/files`.
+/// [`Flatten::Repeated`] flattens the only entry of `dir` for as long as it is a directory,
+/// eg. `App/App/App-v1.2/files -> App/files`.
+///
+/// # Errors
+///
+/// Will return `Err` if `dir` or an inner directory could not be read,
+/// or its contents could not be moved.
+pub fn flatten_dir(name: impl AsRef<str>, dir: &Path, strategy: Flatten) -> Result<(), Error> {
+    let name = name.as_ref();
+
+    match strategy {
+        Flatten::Off => println!("Not flattening install directory."),
+        Flatten::Keyword => {
+            let inner_dir = dir
+                .read_dir()
+                .map_err(|err| Error::Flatten(dir.to_path_buf(), err))?
+                .filter_map(Result::ok)
+                .find(|d| d.path().is_dir() && is_inner_dir(name, &d.path()));
+
+            match inner_dir {
+                Some(inner_dir) => {
+                    flatten_into(&inner_dir.path(), dir)?;
+                }
+                None => println!("No inner directory to flatten."),
+            }
         }
+        Flatten::Repeated => {
+            let mut levels = 0;
+            while let Some(inner_dir) = only_dir(dir)? {
+                if !flatten_into(&inner_dir, dir)? {
+                    break;
+                }
+                levels += 1;
+            }
 
-        flattened += 1;
-        print_flush!("Flattened {flattened} file(s)\r");
+            if levels == 0 {
+                println!("No inner directory to flatten.");
+            }
+        }
     }
 
-    if let Err(err) = fs::remove_dir(inner_dir.path()) {
+    Ok(())
+}
+
+/// The only entry of `dir`, if it is a directory.
+fn only_dir(dir: &Path) -> Result<Option<PathBuf>, Error> {
+    let mut entries = dir
+        .read_dir()
+        .map_err(|err| Error::Flatten(dir.to_path_buf(), err))?;
+
+    let (Some(entry), None) = (entries.next(), entries.next()) else {
+        return Ok(None);
+    };
+    let entry = entry.map_err(|err| Error::Flatten(dir.to_path_buf(), err))?;
+
+    // a symlink to a directory is left alone, its target could be anywhere.
+    let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+    Ok(is_dir.then(|| entry.path()))
+}
+
+/// Move all contents of `inner_dir` to `dir`, then remove `inner_dir`.
+///
+/// Nothing is moved if any of the contents would replace something already in `dir`,
+/// apart from `inner_dir` itself, which is moved out of the way first.
+/// Returns whether `inner_dir` was flattened.
+///
+/// # Errors
+///
+/// Will return `Err` if `inner_dir` could not be read, its contents could not be moved,
+/// or it could not be removed after moving them.
+pub fn flatten_into(inner_dir: &Path, dir: &Path) -> Result<bool, Error> {
+    let flatten_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |err| Error::Flatten(path, err)
+    };
+
+    let names = inner_dir
+        .read_dir()
+        .map_err(flatten_error(inner_dir))?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(flatten_error(inner_dir))?;
+
+    let mut renamed = false;
+    let mut collisions = Vec::new();
+    for name in &names {
+        let moved_path = dir.join(name);
+        if moved_path == inner_dir {
+            renamed = true;
+        } else if fs::symlink_metadata(&moved_path).is_ok() {
+            collisions.push(moved_path);
+        }
+    }
+
+    if !collisions.is_empty() {
         println!(
-            "Got error {:?} while removing inner folder {:?}",
-            err.kind(),
-            inner_dir.path()
+            "Not flattening {inner_dir:?}, it would replace {} file(s) and folder(s): {collisions:?}",
+            collisions.len()
         );
+        return Ok(false);
+    }
+
+    // `App/App/App` can only be moved to `App/App` once the inner `App` is out of the way.
+    let inner_dir = if renamed {
+        let mut temp_name = inner_dir.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".flattening");
+        let temp_dir = dir.join(temp_name);
+
+        fs::rename(inner_dir, &temp_dir).map_err(flatten_error(inner_dir))?;
+        temp_dir
     } else {
-        println!("Sucessfully flattened {flattened} file(s).\n");
+        inner_dir.to_path_buf()
+    };
+
+    for (flattened, name) in names.iter().enumerate() {
+        let inner_entry_path = inner_dir.join(name);
+        fs::rename(&inner_entry_path, dir.join(name)).map_err(flatten_error(&inner_entry_path))?;
+
+        print_flush!("Flattened {} file(s)\r", flattened + 1);
     }
-}
 
-/// Create shortcut from executable found in `destination`.
-///
-/// We want to fail silently, so this function returns `()`.
-pub fn create_shortcut(app_name: &str, destination: &Path, no_interaction: bool) {
-    let executables =
-        glob(&destination.join("*exe").to_string_lossy()).expect("Invalid glob pattern used");
-    let executables: Vec<PathBuf> = executables.filter_map(Result::ok).collect();
+    fs::remove_dir(&inner_dir).map_err(flatten_error(&inner_dir))?;
 
-    let executable: PathBuf = if executables.is_empty() {
-        // skip to end
-        if no_interaction {
-            println!("Could not find any installed executables.");
-            return;
-        }
+    println!("Sucessfully flattened {} file(s).\n", names.len());
+    Ok(true)
+}
 
-        print_flush!("No installed executables could be found. (s)kip creating shortcut or (g)ive path manually? ");
+/// Returns true if `dir` is named after the app `name`, so [`flatten_dir`] would flatten it.
+#[must_use]
+pub fn is_inner_dir(name: &str, dir: &Path) -> bool {
+    name_has_keywords(name.split(' '), dir)
+}
 
-        if prompt().to_lowercase() == "g" {
-            prompt_user_for_path(destination)
-        } else {
-            return;
-        }
-    } else if let Some(found_executable) = executables
+/// Find the executable named after the app `app_name` in `executables`,
+/// the one [`find_shortcut_executable`] suggests first.
+#[must_use]
+pub fn find_app_executable<'a>(app_name: &str, executables: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    executables
         .iter()
         .find(|p| name_has_keywords(app_name.split(' '), p))
-    {
-        // assume yes
-        if no_interaction {
-            println!("Found executable {:?}", &found_executable);
-            dunce::canonicalize(found_executable.clone()).expect("Executable path should exist.")
-        } else {
-            print_flush!(
-                "Found executable {:?}, is it correct? (y/n): ",
-                &found_executable
-            );
+}
 
-            if prompt().to_lowercase() == "y" {
-                found_executable.clone()
-            } else {
-                if executables.len() == 1 {
-                    println!("Found only 1 executable, cannot create shortcut.");
-                    return;
-                }
+/// Find the executable in `dir` to make a shortcut to, returning its path relative to `dir`,
+/// so it can be found before the app is moved to its destination.
+///
+/// The executable set in `profile` is used instead of finding or asking for one.
+/// Returns `Ok(None)` if no executable was found or chosen.
+///
+/// # Errors
+///
+/// Will return `Err` if `stdin` could not be read, or the executable set in `profile` does not exist.
+pub fn find_shortcut_executable(
+    app_name: &str,
+    dir: &Path,
+    profile: &ShortcutProfile,
+    interactor: &dyn Interactor,
+) -> Result<Option<PathBuf>, Error> {
+    if let Some(executable) = &profile.executable {
+        if !dir.join(executable).is_file() {
+            return Err(Error::Shortcut(format!(
+                "{executable:?} set in the profile does not exist"
+            )));
+        }
 
-                println!("\nExecutables found:");
-                for (n, executable) in executables.iter().enumerate() {
-                    println!("{}: {executable:?}", n + 1);
-                }
+        println!("Using executable {executable:?} set in the profile");
+        return Ok(Some(executable.clone()));
+    }
+
+    let Some(executable) = choose_executable(app_name, dir, interactor)? else {
+        return Ok(None);
+    };
 
-                let choice: usize = prompt_user_for_usize(executables.len());
-                let choice = executables
-                    .get(choice - 1)
-                    .expect("should be less than # of executables");
+    // a path that was asked for is canonical, so `dir` has to be too.
+    let dir = dunce::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let executable = dunce::canonicalize(&executable).unwrap_or(executable);
+    executable
+        .strip_prefix(&dir)
+        .map(|executable| Some(executable.to_path_buf()))
+        .map_err(|_| Error::Shortcut(format!("{executable:?} is not inside the install folder")))
+}
+
+/// Create a shortcut to `executable`, relative to `destination`, returning the path of the shortcut.
+///
+/// The name and arguments set in `profile` are used if there are any.
+///
+/// # Errors
+///
+/// Will return `Err` if the shortcut could not be created.
+pub fn create_shortcut(
+    app_name: &str,
+    destination: &Path,
+    executable: &Path,
+    profile: &ShortcutProfile,
+) -> Result<PathBuf, Error> {
+    let canonicalize = |path: &Path| {
+        dunce::canonicalize(path).map_err(|err| Error::Shortcut(format!("{path:?}: {err}")))
+    };
+
+    platform::create_shortcut(
+        profile.name.as_deref().unwrap_or(app_name),
+        &canonicalize(&destination.join(executable))?,
+        &profile.args,
+        &canonicalize(destination)?,
+    )
+}
+
+/// Find the executable in `destination` to make a shortcut to, asking the user when it is not clear.
+///
+/// Returns `Ok(None)` if no executable was found or chosen.
+fn choose_executable(
+    app_name: &str,
+    destination: &Path,
+    interactor: &dyn Interactor,
+) -> Result<Option<PathBuf>, Error> {
+    let mut executables: Vec<PathBuf> = destination
+        .read_dir()
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && platform::is_executable(path))
+                .collect()
+        })
+        .unwrap_or_default();
+    executables.sort();
+
+    let executable: PathBuf = if executables.is_empty() {
+        let Some(executable) =
+            interactor.ask_path("No installed executables could be found.", destination)?
+        else {
+            println!("Could not find any installed executables.");
+            return Ok(None);
+        };
 
-                dunce::canonicalize(choice.clone()).expect("Chosen executable path should exist.")
+        executable
+    } else if let Some(found_executable) = find_app_executable(app_name, &executables) {
+        if interactor.confirm(&format!(
+            "Found executable {found_executable:?}, is it correct?"
+        ))? {
+            found_executable.clone()
+        } else {
+            if executables.len() == 1 {
+                println!("Found only 1 executable, cannot create shortcut.");
+                return Ok(None);
             }
+
+            let options: Vec<String> = executables
+                .iter()
+                .map(|executable| format!("{executable:?}"))
+                .collect();
+
+            let Some(choice) = interactor.choose("Executables found:", &options)? else {
+                println!("No executable chosen, not creating shortcut.");
+                return Ok(None);
+            };
+
+            executables[choice].clone()
         }
     } else {
         println!("Found only 1 executable: {:?}", executables[0]);
-        dunce::canonicalize(executables[0].clone()).expect("Executable path should exist.")
+        executables[0].clone()
     };
 
-    let appdata = std::env::var("APPDATA").expect("Could not find environment variable APPDATA");
-    let start_menu = PathBuf::from(appdata).join(r"Microsoft\Windows\Start Menu\Programs");
+    Ok(Some(executable))
+}
 
-    let shortcut = start_menu.join(format!("{app_name}.lnk"));
-    let Ok(shortcut_dir) = dunce::canonicalize(destination) else {
-        return;
-    };
+/// Run each of `commands` with the shell in `dir`, stopping at the first that fails.
+///
+/// Unless the commands are `trusted`, they are only run if `interactor` confirms,
+/// since a profile found next to the parts comes from whoever made them.
+///
+/// # Errors
+///
+/// Will return `Err` if a command could not be run or exited unsuccessfully, or the answer could not be read.
+pub fn run_hooks(
+    commands: &[String],
+    dir: &Path,
+    trusted: bool,
+    interactor: &dyn Interactor,
+) -> Result<(), Error> {
+    println!("\nPost-install commands:");
+    for command in commands {
+        println!("  {command}");
+    }
 
-    // create a shortcut in powershell
-    let script = format!(
-        // do not need quotes around placeholder since PathBuf's Debug impl adds quotes
-        r"$shortcut = (New-Object -COMObject WScript.Shell).CreateShortcut({shortcut:?});
-            $shortcut.TargetPath = {executable:?};
-            $shortcut.WorkingDirectory = {shortcut_dir:?};
-            $shortcut.Save()",
-    );
+    if !trusted && !interactor.confirm_untrusted("Run these commands from the app's profile?")? {
+        println!("Not running post-install commands.");
+        return Ok(());
+    }
+
+    for command in commands {
+        println!("Running `{command}`");
 
-    let powershell = Command::new("powershell")
-        .args(["-c", &script])
-        .status()
-        .expect("Failed to run powershell.");
+        let status = platform::shell(command)
+            .current_dir(dir)
+            .status()
+            .map_err(|err| Error::Hook(command.clone(), err))?;
 
-    match powershell.code() {
-        Some(0) => println!("Successfully created shortcut to {executable:?}."),
-        Some(1) => {
-            println!("Powershell encountered an uncaught error while creating the shortcut.");
+        if !status.success() {
+            return Err(Error::Hook(
+                command.clone(),
+                io::Error::other(format!("exited with {status}")),
+            ));
         }
-        code => println!("Powershell exit code: {code:?}"),
     }
+
+    Ok(())
+}
+
+/// Remove `files`, returning the ones removed and their total size.
+///
+/// Files that could not be removed are reported and kept, since the install itself already succeeded.
+pub fn clean_up(files: &[PathBuf]) -> (Vec<PathBuf>, u64) {
+    let mut removed = Vec::new();
+    let mut reclaimed = 0;
+
+    for file in files {
+        let size = fs::metadata(file).map_or(0, |metadata| platform::file_size(&metadata));
+
+        match fs::remove_file(file) {
+            Ok(()) => {
+                println!("Removed {file:?}");
+                removed.push(file.clone());
+                reclaimed += size;
+            }
+            Err(err) => eprintln!("Could not remove {file:?}: {err}"),
+        }
+    }
+
+    (removed, reclaimed)
 }
@@ -1,6 +1,9 @@
 use std::path::Path;
 
-use crate::PathExt;
+use crate::{
+    extract::{ExternalExtractor, ExtractError, SevenZip, Unrar},
+    PathExt,
+};
 
 #[test]
 fn test_archive_ext() {
@@ -40,3 +43,1245 @@ fn test_numeric_ext() {
     let empty = Path::new("");
     assert!(!empty.is_numeric());
 }
+
+#[test]
+fn test_platform_file_size() {
+    let metadata = std::fs::metadata("Cargo.toml").unwrap();
+    assert_eq!(crate::platform::file_size(&metadata), metadata.len());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_desktop_entry() {
+    let entry = crate::platform::desktop_entry(
+        "Test App",
+        Path::new("/opt/Test App/run $1.sh"),
+        &[],
+        Path::new("/opt/Test App"),
+        "/opt/Test App/icon.png",
+    );
+
+    assert_eq!(
+        entry,
+        "[Desktop Entry]\nType=Application\nName=Test App\nExec=\"/opt/Test App/run \\\\$1.sh\"\nPath=/opt/Test App\nIcon=/opt/Test App/icon.png\nTerminal=false\n"
+    );
+
+    let entry = crate::platform::desktop_entry(
+        "Test App",
+        Path::new("/opt/Test App/run"),
+        &["--scale".to_string(), "100%".to_string()],
+        Path::new("/opt/Test App"),
+        "application-x-executable",
+    );
+    assert!(entry.contains("\nExec=\"/opt/Test App/run\" \"--scale\" \"100%%\"\n"));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn test_native_extract_tar() {
+    use crate::extract::{Answers, Extractor, Native};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-native-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let archive = dir.join("app.tar");
+    let mut builder = tar::Builder::new(std::fs::File::create(&archive).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(5);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "app/file.txt", &b"hello"[..])
+        .unwrap();
+    builder.finish().unwrap();
+    drop(builder);
+
+    let destination = dir.join("out");
+    let progress = crate::progress::Progress::new("Extracting");
+    Native
+        .extract(&archive, &destination, Answers::Yes, None, &progress)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(destination.join("app/file.txt")).unwrap(),
+        b"hello"
+    );
+
+    assert!(matches!(
+        Native.extract(
+            &dir.join("app.rar"),
+            &destination,
+            Answers::Yes,
+            None,
+            &progress
+        ),
+        Err(ExtractError::Unsupported(_, _))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_extractor_answers() {
+    use std::{ffi::OsString, path::Path};
+
+    use crate::extract::{Answers, Bsdtar, ExternalExtractor, SevenZip, Unrar};
+
+    let args = |extractor: &dyn ExternalExtractor, answers| {
+        extractor.args(Path::new("app.7z"), Path::new("out"), answers, None)
+    };
+    let yes = OsString::from("-y");
+
+    // only answers that are assumed yes are passed on as yes.
+    assert!(args(&SevenZip, Answers::Yes).contains(&yes));
+    assert!(args(&Unrar, Answers::Yes).contains(&yes));
+    for answers in [Answers::Ask, Answers::No] {
+        assert!(!args(&SevenZip, answers).contains(&yes), "{answers:?}");
+        assert!(!args(&Unrar, answers).contains(&yes), "{answers:?}");
+    }
+    assert!(args(&SevenZip, Answers::No).contains(&OsString::from("-aos")));
+    assert!(args(&Unrar, Answers::No).contains(&OsString::from("-o-")));
+
+    // bsdtar overwrites unless told to keep existing files.
+    let keep = OsString::from("-k");
+    assert!(args(&Bsdtar, Answers::No).contains(&keep));
+    for answers in [Answers::Ask, Answers::Yes] {
+        assert!(!args(&Bsdtar, answers).contains(&keep), "{answers:?}");
+    }
+}
+
+/// Write a tar of `files`, each containing `hello` with the mode given,
+/// split in two parts named `{name}.tar.001` and `{name}.tar.002` in `dir`.
+#[cfg(all(unix, feature = "native"))]
+fn write_split_tar(dir: &Path, name: &str, files: &[(&str, u32)]) {
+    let mut builder = tar::Builder::new(Vec::new());
+    for &(path, mode) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, &b"hello"[..])
+            .unwrap();
+    }
+    let archive = builder.into_inner().unwrap();
+
+    let (first, second) = archive.split_at(archive.len() / 2);
+    std::fs::write(dir.join(format!("{name}.tar.001")), first).unwrap();
+    std::fs::write(dir.join(format!("{name}.tar.002")), second).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_plan() {
+    use crate::{
+        extract::ExtractorKind, profile::PROFILE_NAME, ArchiveContents, Flatten, Installer,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-plan-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("dest")).unwrap();
+
+    write_split_tar(
+        &dir,
+        "App",
+        &[("./App/run", 0o755), ("./App/data.txt", 0o644)],
+    );
+
+    let plan = Installer::new("App", dir.join("dest"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .plan()
+        .unwrap();
+
+    let destination = dir.join("dest").join("App");
+    assert_eq!(
+        plan.parts,
+        [dir.join("App.tar.001"), dir.join("App.tar.002")]
+    );
+    assert_eq!(plan.archive, Some(dir.join("App.tar")));
+    assert!(plan.combine);
+    assert_eq!(
+        plan.contents,
+        ArchiveContents::Listed {
+            entries: 2,
+            inner_dir: Some(destination.join("App")),
+            executable: Some(destination.join("run")),
+        }
+    );
+
+    // nothing is written.
+    assert!(!dir.join("App.tar").exists());
+    assert!(!destination.exists());
+
+    // a profile's strategy is used unless one is set explicitly.
+    std::fs::write(dir.join(PROFILE_NAME), "flatten = \"off\"\n").unwrap();
+    let installer = || {
+        Installer::new("App", dir.join("dest"))
+            .working_dir(&dir)
+            .extractor(Some(ExtractorKind::Native))
+    };
+    assert_eq!(installer().plan().unwrap().flatten, Flatten::Off);
+    assert_eq!(
+        installer()
+            .flatten(Flatten::Keyword)
+            .plan()
+            .unwrap()
+            .flatten,
+        Flatten::Keyword
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_profile_folder() {
+    use crate::{
+        extract::ExtractorKind, interact::Scripted, profile::PROFILE_NAME, uninstall::uninstall,
+        Installer, Interaction,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-folder-{}", std::process::id()));
+    let state_dir = dir.join("state");
+    std::fs::create_dir_all(dir.join("dest")).unwrap();
+
+    write_split_tar(&dir, "App", &[("./App/run", 0o755)]);
+    std::fs::write(
+        dir.join(PROFILE_NAME),
+        "folder = \"My App\"\npost_install = [\"touch ran\"]\n",
+    )
+    .unwrap();
+
+    let report = Installer::new("App", dir.join("dest"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .shortcut(false)
+        .manifest_dir(Some(state_dir.clone()))
+        .interaction(Interaction::AssumeYes)
+        .run()
+        .unwrap();
+    assert_eq!(report.destination, dir.join("dest").join("My App"));
+    assert!(report.destination.join("run").is_file());
+    // commands from a profile found next to the parts are not run without a user confirming them.
+    assert!(!report.destination.join("ran").exists());
+
+    // the app is still uninstalled by its name.
+    uninstall("App", &state_dir, &Scripted::new(["y"])).unwrap();
+    assert!(!report.destination.exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_install() {
+    use crate::{extract::ExtractorKind, interact::Scripted, manifest::Manifest, Error, Installer};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-install-{}", std::process::id()));
+    let state_dir = dir.join("state");
+    std::fs::create_dir_all(dir.join("dest")).unwrap();
+
+    write_split_tar(
+        &dir,
+        "App",
+        &[("./App/bin/run", 0o755), ("./App/data.txt", 0o644)],
+    );
+
+    let installer = |answers: &[&str]| {
+        Installer::new("App", dir.join("dest"))
+            .working_dir(&dir)
+            .extractor(Some(ExtractorKind::Native))
+            .shortcut(false)
+            .manifest_dir(Some(state_dir.clone()))
+            .interactor(Scripted::new(answers.to_vec()))
+    };
+
+    let report = installer(&[]).run().unwrap();
+    let destination = dir.join("dest").join("App");
+    assert_eq!(report.destination, destination);
+    assert_eq!(report.archive, Some(dir.join("App.tar")));
+    assert_eq!(
+        std::fs::read(destination.join("bin/run")).unwrap(),
+        b"hello"
+    );
+    assert!(destination.join("data.txt").is_file());
+
+    let manifest = Manifest::load(&report.manifest.unwrap()).unwrap();
+    assert_eq!(manifest.app_name, "App");
+    assert_eq!(manifest.destination, destination);
+    assert_eq!(
+        manifest.parts,
+        [dir.join("App.tar.001"), dir.join("App.tar.002")]
+    );
+    let mut files: Vec<_> = manifest.files.iter().map(|file| &file.path).collect();
+    files.sort();
+    assert_eq!(files, [Path::new("bin/run"), Path::new("data.txt")]);
+
+    // only the installed app is left in the destination, no staging folder.
+    let entries: Vec<_> = std::fs::read_dir(dir.join("dest"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, ["App"]);
+
+    // streaming the parts skips the archive the install combined next to them.
+    std::fs::create_dir(dir.join("streamed")).unwrap();
+    let streamed = Installer::new("App", dir.join("streamed"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .stream(true)
+        .shortcut(false)
+        .manifest_dir(None)
+        .interactor(Scripted::new::<&str>([]))
+        .run()
+        .unwrap();
+    assert!(streamed.destination.join("bin/run").is_file());
+    std::fs::remove_dir_all(dir.join("streamed")).unwrap();
+
+    // installing again asks to extract the combined archive, and to continue into the installed app.
+    let result = installer(&["y", "n"]).run();
+    assert!(matches!(result, Err(Error::Cancelled)), "{result:?}");
+    assert!(destination.join("bin/run").is_file());
+
+    // a corrupt second part fails the install, and nothing is left behind.
+    std::fs::remove_dir_all(&destination).unwrap();
+    std::fs::remove_dir_all(&state_dir).unwrap();
+    std::fs::remove_file(dir.join("App.tar")).unwrap();
+    let second = std::fs::read(dir.join("App.tar.002")).unwrap();
+    std::fs::write(dir.join("App.tar.002"), &second[..second.len() / 4]).unwrap();
+
+    let result = installer(&[]).run();
+    assert!(matches!(result, Err(Error::Extract(_))), "{result:?}");
+    assert_eq!(std::fs::read_dir(dir.join("dest")).unwrap().count(), 0);
+    assert!(!state_dir.exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_cleanup() {
+    use crate::{extract::ExtractorKind, Cleanup, Installer, Interaction};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-cleanup-{}", std::process::id()));
+
+    for (cleanup, removed) in [
+        (Cleanup::None, &[][..]),
+        (Cleanup::Combined, &["App.tar"][..]),
+        (Cleanup::Parts, &["App.tar.001", "App.tar.002"][..]),
+        (Cleanup::All, &["App.tar", "App.tar.001", "App.tar.002"][..]),
+    ] {
+        std::fs::create_dir_all(dir.join("dest")).unwrap();
+        write_split_tar(&dir, "App", &[("./App/run", 0o755)]);
+        let part_size = |n| {
+            std::fs::metadata(dir.join(format!("App.tar.{n:03}")))
+                .unwrap()
+                .len()
+        };
+        // the combined archive is as large as its parts together.
+        let sizes = [
+            ("App.tar", part_size(1) + part_size(2)),
+            ("App.tar.001", part_size(1)),
+            ("App.tar.002", part_size(2)),
+        ];
+
+        let report = Installer::new("App", dir.join("dest"))
+            .working_dir(&dir)
+            .extractor(Some(ExtractorKind::Native))
+            .shortcut(false)
+            .manifest_dir(None)
+            .cleanup(cleanup)
+            .interaction(Interaction::AssumeYes)
+            .run()
+            .unwrap();
+        assert!(report.destination.join("run").is_file(), "{cleanup:?}");
+
+        let mut cleaned = report.cleaned.clone();
+        cleaned.sort();
+        let removed_paths: Vec<_> = removed.iter().map(|name| dir.join(name)).collect();
+        assert_eq!(cleaned, removed_paths, "{cleanup:?}");
+
+        for (name, _) in sizes {
+            assert_eq!(
+                dir.join(name).exists(),
+                !removed.contains(&name),
+                "{cleanup:?} {name}"
+            );
+        }
+
+        let reclaimed: u64 = sizes
+            .iter()
+            .filter(|(name, _)| removed.contains(name))
+            .map(|(_, size)| size)
+            .sum();
+        assert_eq!(report.reclaimed, reclaimed, "{cleanup:?}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_shortcut_checked_before_install() {
+    use crate::{extract::ExtractorKind, profile::PROFILE_NAME, Error, Installer, Interaction};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-missing-{}", std::process::id()));
+    let state_dir = dir.join("state");
+    std::fs::create_dir_all(dir.join("dest")).unwrap();
+
+    write_split_tar(&dir, "App", &[("./App/run", 0o755)]);
+    std::fs::write(
+        dir.join(PROFILE_NAME),
+        "[shortcut]\nexecutable = \"missing\"\n",
+    )
+    .unwrap();
+
+    let result = Installer::new("App", dir.join("dest"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .manifest_dir(Some(state_dir.clone()))
+        .interaction(Interaction::AssumeYes)
+        .run();
+    assert!(matches!(result, Err(Error::Shortcut(_))));
+
+    // the staged files are removed, and nothing was installed to uninstall.
+    assert_eq!(std::fs::read_dir(dir.join("dest")).unwrap().count(), 0);
+    assert!(!state_dir.exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_uninstall_keeps_existing() {
+    use crate::{
+        extract::ExtractorKind, interact::Scripted, manifest::Manifest, uninstall::uninstall,
+        Installer, Interaction,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-existing-{}", std::process::id()));
+    let state_dir = dir.join("state");
+    let destination = dir.join("dest").join("App");
+    std::fs::create_dir_all(&destination).unwrap();
+    std::fs::write(destination.join("user-save.dat"), "").unwrap();
+
+    write_split_tar(&dir, "App", &[("./App/run", 0o755)]);
+
+    // continuing into a destination that is not empty is confirmed.
+    let report = Installer::new("App", dir.join("dest"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .shortcut(false)
+        .manifest_dir(Some(state_dir.clone()))
+        .interaction(Interaction::AssumeYes)
+        .run()
+        .unwrap();
+    assert!(destination.join("run").is_file());
+
+    let manifest = Manifest::load(&report.manifest.unwrap()).unwrap();
+    let files: Vec<_> = manifest.files.iter().map(|file| &file.path).collect();
+    assert_eq!(files, [Path::new("run")]);
+
+    let answers = Scripted::new(["y", "n"]);
+    let uninstalled = uninstall("App", &state_dir, &answers).unwrap();
+    assert!(answers.is_finished());
+    assert_eq!(uninstalled.removed_files, 1);
+    assert!(!destination.join("run").exists());
+    assert!(destination.join("user-save.dat").is_file());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "native"))]
+#[test]
+fn test_uninstall_assume_yes_keeps_existing() {
+    use crate::{
+        extract::ExtractorKind, interact::AssumeYes, uninstall::uninstall, Installer, Interaction,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-assume-{}", std::process::id()));
+    let state_dir = dir.join("state");
+    let destination = dir.join("dest").join("App");
+    std::fs::create_dir_all(&destination).unwrap();
+    std::fs::write(destination.join("user-save.dat"), "").unwrap();
+
+    write_split_tar(&dir, "App", &[("./App/run", 0o755)]);
+
+    Installer::new("App", dir.join("dest"))
+        .working_dir(&dir)
+        .extractor(Some(ExtractorKind::Native))
+        .shortcut(false)
+        .manifest_dir(Some(state_dir.clone()))
+        .interaction(Interaction::AssumeYes)
+        .run()
+        .unwrap();
+
+    // removing files that were not installed is never assumed.
+    let uninstalled = uninstall("App", &state_dir, &AssumeYes).unwrap();
+    assert_eq!(uninstalled.removed_files, 1);
+    assert_eq!(uninstalled.kept, [destination.join("user-save.dat")]);
+    assert!(!destination.join("run").exists());
+    assert!(destination.join("user-save.dat").is_file());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_profile() {
+    use crate::{
+        extract::ExtractorKind,
+        profile::{Profile, PROFILE_NAME},
+        Error, Flatten,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-profile-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    assert_eq!(Profile::find(&dir), None);
+
+    let path = dir.join(PROFILE_NAME);
+    std::fs::write(
+        &path,
+        "folder = \"My App\"\nflatten = false\nextractor = \"7z\"\npassword_file = \"pw.txt\"\n\n[shortcut]\nexecutable = \"bin/app\"\nargs = [\"--portable\"]\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("pw.txt"), "hunter2\n").unwrap();
+
+    let profile = Profile::load(&Profile::find(&dir).unwrap()).unwrap();
+    assert_eq!(profile.folder.as_deref(), Some("My App"));
+    assert_eq!(profile.flatten, Some(Flatten::Off));
+    assert_eq!(profile.extractor, Some(ExtractorKind::SevenZip));
+    assert_eq!(
+        profile.shortcut.executable.as_deref(),
+        Some(Path::new("bin/app"))
+    );
+    assert_eq!(profile.shortcut.args, ["--portable"]);
+    assert_eq!(profile.shortcut.name, None);
+    assert_eq!(profile.password().unwrap().as_deref(), Some("hunter2"));
+
+    for invalid in [
+        "extractor = \"winzip\"",
+        "flaten = false",
+        "folder = \"../outside\"",
+        "folder = \"/tmp\"",
+        "folder = \"a/b\"",
+        "flatten_dir = \"bin/../../..\"",
+        "[shortcut]\nname = \"../app\"",
+        "[shortcut]\nexecutable = \"/bin/sh\"",
+        "password_file = \"/etc/shadow\"",
+        "password_file = \"../secret.txt\"",
+        "password_file = \"./pw.txt\"",
+    ] {
+        std::fs::write(&path, invalid).unwrap();
+        assert!(matches!(Profile::load(&path), Err(Error::Profile(..))));
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_flatten() {
+    use std::fs;
+
+    use crate::{steps::flatten_dir, Flatten};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-flatten-{}", std::process::id()));
+
+    // a wrapper with an unrelated name, then one holding a folder with its own name.
+    let app = dir.join("App");
+    fs::create_dir_all(app.join("dist/App/App")).unwrap();
+    fs::write(app.join("dist/App/App/run"), "").unwrap();
+    fs::write(app.join("dist/App/readme.txt"), "").unwrap();
+
+    flatten_dir("App", &app, Flatten::Repeated).unwrap();
+    assert!(app.join("App/run").is_file());
+    assert!(app.join("readme.txt").is_file());
+    assert!(!app.join("dist").exists());
+
+    // an inner entry that would replace one already there stops flattening instead of being lost.
+    fs::write(app.join("App/readme.txt"), "inner").unwrap();
+    flatten_dir("App", &app, Flatten::Keyword).unwrap();
+    assert!(app.join("App/readme.txt").is_file());
+    assert!(app.join("App/run").is_file());
+
+    flatten_dir("App", &app, Flatten::Off).unwrap();
+    assert!(app.join("App").is_dir());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_split_destination() {
+    use std::path::PathBuf;
+
+    use crate::split_destination;
+
+    let mut names = vec![PathBuf::from("A"), PathBuf::from("B")];
+    assert_eq!(
+        split_destination(&mut names, None, false),
+        Some(PathBuf::from("B"))
+    );
+    assert_eq!(names, [PathBuf::from("A")]);
+
+    // with a destination from the environment or config, every name is an app,
+    let mut names = vec![PathBuf::from("A"), PathBuf::from("B")];
+    assert_eq!(
+        split_destination(&mut names, Some(PathBuf::from("D:/Apps")), false),
+        Some(PathBuf::from("D:/Apps"))
+    );
+    assert_eq!(names, [PathBuf::from("A"), PathBuf::from("B")]);
+
+    // unless the last is an existing directory without parts, given on the command line instead.
+    let dir = std::env::temp_dir().join(format!("partsinstall-split-{}", std::process::id()));
+    let apps = dir.join("Apps");
+    std::fs::create_dir_all(&apps).unwrap();
+    std::fs::write(apps.join("Game.7z.001"), "").unwrap();
+    let mut names = vec![PathBuf::from("A"), apps.clone()];
+    assert_eq!(
+        split_destination(&mut names, Some(PathBuf::from("D:/Apps")), false),
+        Some(apps.clone())
+    );
+    assert_eq!(names, [PathBuf::from("A")]);
+
+    // but not if it holds its own parts, or `--destination` was given.
+    std::fs::write(apps.join("Apps.7z.001"), "").unwrap();
+    let mut names = vec![PathBuf::from("A"), apps.clone()];
+    assert_eq!(
+        split_destination(&mut names, Some(PathBuf::from("D:/Apps")), false),
+        Some(PathBuf::from("D:/Apps"))
+    );
+    std::fs::remove_file(apps.join("Apps.7z.001")).unwrap();
+    let mut names = vec![PathBuf::from("A"), apps.clone()];
+    assert_eq!(
+        split_destination(&mut names, Some(PathBuf::from("D:/Apps")), true),
+        Some(PathBuf::from("D:/Apps"))
+    );
+    assert_eq!(names.len(), 2);
+
+    assert_eq!(split_destination(&mut Vec::new(), None, false), None);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_config() {
+    use crate::{config::Config, Error};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("config.toml");
+    assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+    std::fs::write(
+        &path,
+        "destination = \"D:/Apps\"\nno-shortcut = true\nnames = [\"a\", 1]\n",
+    )
+    .unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.path.as_deref(), Some(path.as_path()));
+    assert_eq!(
+        config.get("destination"),
+        Some(&["D:/Apps".to_string()][..])
+    );
+    assert_eq!(config.get("no_shortcut"), Some(&["true".to_string()][..]));
+    assert_eq!(config.get("no-shortcut"), None);
+    assert_eq!(
+        config.get("names"),
+        Some(&["a".to_string(), "1".to_string()][..])
+    );
+
+    for invalid in ["cleanup = { all = true }", "names = [[1]]", "destination ="] {
+        std::fs::write(&path, invalid).unwrap();
+        assert!(matches!(Config::load(&path), Err(Error::Config(..))));
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_exit_codes() {
+    assert!(SevenZip.map_exit_code(1).is_ok());
+    assert!(matches!(
+        SevenZip.map_exit_code(2),
+        Err(ExtractError::Fatal("7z"))
+    ));
+    assert!(matches!(
+        SevenZip.map_exit_code(3),
+        Err(ExtractError::UnknownExitCode("7z", 3))
+    ));
+    assert!(matches!(
+        Unrar.map_exit_code(11),
+        Err(ExtractError::WrongPassword("unrar"))
+    ));
+
+    assert_eq!(SevenZip.parse_progress(" 45% 12 - App/app.exe"), Some(45));
+    assert_eq!(SevenZip.parse_progress("100%"), Some(100));
+    assert_eq!(SevenZip.parse_progress("Everything is Ok"), None);
+
+    assert!(SevenZip.is_wrong_password("ERROR: Cannot open encrypted archive. Wrong password?\n"));
+    assert!(!SevenZip.is_wrong_password("ERROR: Data Error : app.exe\n"));
+}
+
+#[test]
+fn test_parts_reader() {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use crate::parts::PartsReader;
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-parts-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let parts: Vec<_> = ["abc", "", "defg", "h"]
+        .iter()
+        .enumerate()
+        .map(|(n, contents)| {
+            let part = dir.join(format!("app.7z.{:03}", n + 1));
+            std::fs::write(&part, contents).unwrap();
+            part
+        })
+        .collect();
+
+    let mut reader = PartsReader::open(&parts).unwrap();
+    assert_eq!(reader.len(), 8);
+
+    let mut all = String::new();
+    reader.read_to_string(&mut all).unwrap();
+    assert_eq!(all, "abcdefgh");
+
+    let mut buf = [0; 3];
+    reader.seek(SeekFrom::Start(2)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"cde");
+
+    reader.seek(SeekFrom::End(-2)).unwrap();
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "gh");
+
+    assert!(reader.seek(SeekFrom::Current(-9)).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_volume_sets() {
+    use std::path::PathBuf;
+
+    use crate::volumes::{volume_stem, VolumeKind, VolumeSet};
+
+    let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+    let split = VolumeSet::detect(paths(&["app.7z.001", "app.7z.002"]));
+    assert_eq!(split.kind, VolumeKind::Split);
+
+    let single = VolumeSet::detect(paths(&["app.part1.rar"]));
+    assert_eq!(single.kind, VolumeKind::Single);
+
+    let rar_parts = VolumeSet::detect(paths(&["app.part10.rar", "app.part2.rar", "app.part1.rar"]));
+    assert_eq!(rar_parts.kind, VolumeKind::RarParts);
+    assert_eq!(rar_parts.first(), Some(Path::new("app.part1.rar")));
+    assert_eq!(rar_parts.volumes[2], Path::new("app.part10.rar"));
+
+    let rar_legacy = VolumeSet::detect(paths(&["app.r01", "app.r00", "app.rar", "app.nfo"]));
+    assert_eq!(rar_legacy.kind, VolumeKind::RarLegacy);
+    assert_eq!(
+        rar_legacy.volumes,
+        paths(&["app.rar", "app.r00", "app.r01"])
+    );
+
+    let zip = VolumeSet::detect(paths(&["app.z01", "app.z02", "app.zip"]));
+    assert_eq!(zip.kind, VolumeKind::ZipSpanned);
+    assert_eq!(zip.first(), Some(Path::new("app.zip")));
+
+    // the first volume is missing, so these can only be split files.
+    let no_first = VolumeSet::detect(paths(&["app.part2.rar", "app.part3.rar"]));
+    assert_eq!(no_first.kind, VolumeKind::Split);
+
+    assert_eq!(volume_stem("Test.App.part01.rar"), Some("Test.App"));
+    assert_eq!(volume_stem("app.r00"), Some("app"));
+    assert_eq!(volume_stem("app.Z01"), Some("app"));
+    assert_eq!(volume_stem("app.rar"), None);
+    assert_eq!(volume_stem("app.7z.001"), None);
+}
+
+#[test]
+fn test_scripted_prompts() {
+    use crate::{interact::Scripted, steps::create_destination, Error};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-prompts-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // empty destinations do not prompt.
+    let no_answers = Scripted::new::<&str>([]);
+    assert!(create_destination(&dir, &no_answers).is_ok());
+
+    std::fs::write(dir.join("file"), "").unwrap();
+
+    let answers = Scripted::new(["n", "y"]);
+    assert!(matches!(
+        create_destination(&dir, &answers),
+        Err(Error::Cancelled)
+    ));
+    assert!(create_destination(&dir, &answers).is_ok());
+    assert!(answers.is_finished());
+
+    assert!(matches!(
+        create_destination(&dir, &no_answers),
+        Err(Error::Prompt(_))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_staging() {
+    use std::fs;
+
+    use crate::{interact::Scripted, steps::create_destination};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-staging-{}", std::process::id()));
+    let destination = dir.join("App");
+    fs::create_dir_all(&dir).unwrap();
+    let no_answers = Scripted::new::<&str>([]);
+
+    // a failed install leaves nothing behind.
+    let staging = create_destination(&destination, &no_answers).unwrap();
+    fs::write(staging.dir().join("half"), "").unwrap();
+    drop(staging);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    let staging = create_destination(&destination, &no_answers).unwrap();
+    fs::create_dir(staging.dir().join("lib")).unwrap();
+    fs::write(staging.dir().join("lib/new"), "new").unwrap();
+    staging.commit().unwrap();
+    assert!(destination.join("lib/new").is_file());
+
+    // files already in the destination are kept, unless the install replaces them.
+    fs::write(destination.join("lib/old"), "").unwrap();
+    let answers = Scripted::new(["y"]);
+    let staging = create_destination(&destination, &answers).unwrap();
+    fs::create_dir(staging.dir().join("lib")).unwrap();
+    fs::write(staging.dir().join("lib/new"), "newer").unwrap();
+    staging.commit().unwrap();
+    assert!(destination.join("lib/old").is_file());
+    assert_eq!(
+        fs::read_to_string(destination.join("lib/new")).unwrap(),
+        "newer"
+    );
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_scripted_choices() {
+    use crate::interact::{Interactor, Scripted};
+
+    let options = ["a".to_string(), "b".to_string()];
+    let answers = Scripted::new(["2", "3", "s"]);
+
+    assert_eq!(answers.choose("?", &options).unwrap(), Some(1));
+    assert!(answers.choose("?", &options).is_err());
+    assert_eq!(answers.ask_path("?", Path::new(".")).unwrap(), None);
+
+    let answers = Scripted::new([" secret ", ""]);
+    assert_eq!(
+        answers.ask_password("?").unwrap().as_deref(),
+        Some(" secret ")
+    );
+    assert_eq!(answers.ask_password("?").unwrap(), None);
+}
+
+#[test]
+fn test_manifest() {
+    use std::time::Duration;
+
+    use crate::{
+        manifest::{record_files, Flags, Manifest},
+        InstallReport,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-manifest-{}", std::process::id()));
+    let destination = dir.join("App");
+    std::fs::create_dir_all(destination.join("data")).unwrap();
+    std::fs::write(destination.join("app.exe"), "").unwrap();
+    std::fs::write(destination.join("data/file.txt"), "hello").unwrap();
+
+    let report = InstallReport {
+        app_name: "App".to_string(),
+        parts: vec![dir.join("App.7z.001")],
+        archive: None,
+        destination: destination.clone(),
+        shortcut: None,
+        manifest: None,
+        cleaned: Vec::new(),
+        reclaimed: 0,
+        combine_time: Duration::ZERO,
+        extract_time: Duration::ZERO,
+        flatten_time: Duration::ZERO,
+    };
+
+    let files = record_files(&destination).unwrap();
+    let manifest = Manifest::new(&report, files, Flags::default()).unwrap();
+    let files: Vec<_> = manifest.files.iter().map(|file| &file.path).collect();
+    assert_eq!(
+        files,
+        [Path::new("app.exe"), &Path::new("data").join("file.txt")]
+    );
+    assert_eq!(manifest.files[1].size, 5);
+    assert_eq!(
+        manifest.files[1].sha256,
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+
+    let path = manifest.save(&dir.join("state")).unwrap();
+    assert_eq!(Manifest::load(&path).unwrap(), manifest);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_uninstall() {
+    use std::time::Duration;
+
+    use crate::{
+        interact::Scripted,
+        manifest::{record_files, Flags, Manifest},
+        uninstall::uninstall,
+        Error, InstallReport,
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-uninstall-{}", std::process::id()));
+    let destination = dir.join("App");
+    let state_dir = dir.join("state");
+    std::fs::create_dir_all(destination.join("data")).unwrap();
+    std::fs::write(destination.join("app.exe"), "").unwrap();
+    std::fs::write(destination.join("data/file.txt"), "hello").unwrap();
+
+    let report = InstallReport {
+        app_name: "App".to_string(),
+        parts: Vec::new(),
+        archive: None,
+        destination: destination.clone(),
+        shortcut: None,
+        manifest: None,
+        cleaned: Vec::new(),
+        reclaimed: 0,
+        combine_time: Duration::ZERO,
+        extract_time: Duration::ZERO,
+        flatten_time: Duration::ZERO,
+    };
+    let files = record_files(&destination).unwrap();
+    let manifest = Manifest::new(&report, files, Flags::default()).unwrap();
+
+    // a manifest pointing outside of its install is refused before anything is removed.
+    let mut tampered = manifest.clone();
+    tampered.files[0].path = Path::new("..").join("state");
+    tampered.save(&state_dir).unwrap();
+    assert!(matches!(
+        uninstall("App", &state_dir, &Scripted::new(["y"])),
+        Err(Error::OutsideDestination(_))
+    ));
+
+    manifest.save(&state_dir).unwrap();
+    std::fs::write(destination.join("data/save.dat"), "").unwrap();
+
+    let answers = Scripted::new(["y", "n"]);
+    let uninstalled = uninstall("App", &state_dir, &answers).unwrap();
+    assert!(answers.is_finished());
+    assert_eq!(uninstalled.removed_files, 2);
+    assert_eq!(uninstalled.removed_size, 5);
+    assert_eq!(uninstalled.kept, [destination.join("data/save.dat")]);
+    assert!(!destination.join("app.exe").exists());
+    assert!(!Manifest::path(&state_dir, "App").exists());
+
+    assert!(matches!(
+        uninstall("App", &state_dir, &Scripted::new(["y"])),
+        Err(Error::NotInstalled(_))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_list_installed() {
+    use crate::{
+        list::installed,
+        manifest::{Flags, Manifest},
+    };
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-list-{}", std::process::id()));
+    let state_dir = dir.join("state");
+
+    for (app_name, destination) in [("B", "apps"), ("A", "apps"), ("C", "games")] {
+        let destination = dir.join(destination);
+        std::fs::create_dir_all(destination.join(app_name)).unwrap();
+        std::fs::write(destination.join(app_name).join("app.exe"), "abc").unwrap();
+
+        Manifest {
+            version: String::new(),
+            app_name: app_name.to_string(),
+            parts: vec![dir.join(format!("{app_name}.7z.001"))],
+            archive: None,
+            destination: dunce::canonicalize(&destination).unwrap().join(app_name),
+            folder: None,
+            files: Vec::new(),
+            shortcut: None,
+            shortcut_name: None,
+            flags: Flags::default(),
+            installed_at: 0,
+        }
+        .save(&state_dir)
+        .unwrap();
+    }
+
+    let all = installed(&state_dir, None).unwrap();
+    let names: Vec<_> = all.iter().map(|app| app.name.as_str()).collect();
+    assert_eq!(names, ["A", "B", "C"]);
+    assert_eq!(all[0].size, 3);
+    assert_eq!(all[0].source.as_deref(), Some("A.7z.001"));
+
+    let apps = installed(&state_dir, Some(&dir.join("apps"))).unwrap();
+    assert_eq!(apps.len(), 2);
+
+    assert!(installed(&dir.join("missing"), None).unwrap().is_empty());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_checksums() {
+    use std::io::Read;
+
+    use crate::{checksum::Checksums, Error};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-checksums-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let files = [
+        ("release.sfv", "; comment\nApp.7z.001 3610A686\n"),
+        (
+            "release.md5",
+            "5d41402abc4b2a76b9719d911017c592 *sub/App.7z.002\n",
+        ),
+        (
+            "release.sha256",
+            "SHA256 (App.7z.003) = 2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\n",
+        ),
+        (
+            "App.7z.004.sha256",
+            "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824\n",
+        ),
+    ]
+    .map(|(name, contents)| {
+        std::fs::write(dir.join(name), contents).unwrap();
+        dir.join(name)
+    });
+
+    let parts: Vec<_> = (1..=4)
+        .map(|n| dir.join(format!("App.7z.{n:03}")))
+        .collect();
+    let checksums = Checksums::load(&files, &parts).unwrap();
+    assert_eq!(checksums.files(), files);
+    for n in 1..=4 {
+        let part = dir.join(format!("app.7z.{n:03}"));
+        assert!(checksums.get(&part).is_some(), "{part:?}");
+
+        let mut reader = checksums.reader(&part, &b"hello"[..]);
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        reader.finish().unwrap();
+    }
+
+    let mut corrupt = checksums.reader(&dir.join("App.7z.001"), &b"hell0"[..]);
+    corrupt.read_to_end(&mut Vec::new()).unwrap();
+    assert!(matches!(corrupt.finish(), Err(Error::Checksum(..))));
+
+    // lines that are not a checksum or name other files are skipped, and so are files listing none of the parts.
+    std::fs::write(
+        dir.join("mixed.md5"),
+        "not a checksum\n5d41402abc4b2a76b9719d911017c592  Other.7z.001\n5d41402abc4b2a76b9719d911017c592  App.7z.001\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("other.sfv"), "Other.7z.001 3610A686\n").unwrap();
+    let checksums =
+        Checksums::load(&[dir.join("mixed.md5"), dir.join("other.sfv")], &parts).unwrap();
+    assert_eq!(checksums.files(), [dir.join("mixed.md5")]);
+    assert!(checksums.get(&dir.join("App.7z.001")).is_some());
+    assert!(checksums.get(&dir.join("Other.7z.001")).is_none());
+    assert!(matches!(
+        Checksums::load(&[dir.join("missing.md5")], &parts),
+        Err(Error::ChecksumFile(..))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_find_app_names() {
+    use crate::steps::find_app_names;
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-apps-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("Folder.App")).unwrap();
+
+    for file in [
+        "App.7z.001",
+        "App.7z.002",
+        "App.7z",
+        "Game.part1.rar",
+        "Game.part2.rar",
+        "Tool.zip",
+        "readme.txt",
+        "release.sfv",
+    ] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+
+    assert_eq!(find_app_names(&dir).unwrap(), ["App", "Game", "Tool"]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_check_sequence() {
+    use std::path::PathBuf;
+
+    use crate::{steps::check_sequence, Error};
+
+    let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+
+    let mut parts = paths(&["app.7z.002", "app.7z.001", "app.7z.003"]);
+    assert!(check_sequence(&mut parts).is_ok());
+    assert_eq!(parts, paths(&["app.7z.001", "app.7z.002", "app.7z.003"]));
+
+    assert!(check_sequence(&mut paths(&["app.7z.0", "app.7z.1"])).is_ok());
+    let mut unpadded: Vec<_> = (1..=10)
+        .map(|n| PathBuf::from(format!("app.7z.{n}")))
+        .collect();
+    assert!(check_sequence(&mut unpadded).is_ok());
+
+    let missing = check_sequence(&mut paths(&[
+        "app.7z.002",
+        "app.7z.004",
+        "app.7z.005",
+        "app.7z.009",
+    ]));
+    assert!(matches!(&missing, Err(Error::MissingParts(n)) if n == &[1, 3, 6, 7, 8]));
+    assert_eq!(
+        missing.unwrap_err().to_string(),
+        "Parts are missing, download part(s) 1, 3, 6-8"
+    );
+
+    assert!(matches!(
+        check_sequence(&mut paths(&["app.7z.001", "app.zip.001", "app.7z.002"])),
+        Err(Error::DuplicateParts(dupes)) if dupes.len() == 2
+    ));
+    assert!(matches!(
+        check_sequence(&mut paths(&["app.7z.001", "app.7z.02", "app.7z.003"])),
+        Err(Error::MixedPadding(mixed)) if mixed == paths(&["app.7z.02"])
+    ));
+    assert!(matches!(
+        check_sequence(&mut paths(&["app.7z.001", "app.nfo"])),
+        Err(Error::NotNumbered(_))
+    ));
+    assert!(matches!(
+        check_sequence(&mut []),
+        Err(Error::MissingParts(missing)) if missing == [1]
+    ));
+}
+
+#[test]
+fn test_resume_combine() {
+    use std::time::Instant;
+
+    use crate::{checksum::Checksums, interact::Scripted, steps::combine_files, Error};
+
+    let dir = std::env::temp_dir().join(format!("partsinstall-resume-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut parts: Vec<_> = ["abc", "defg", "hi"]
+        .iter()
+        .enumerate()
+        .map(|(n, contents)| {
+            let part = dir.join(format!("app.7z.{:03}", n + 1));
+            std::fs::write(&part, contents).unwrap();
+            part
+        })
+        .collect();
+    let output = dir.join("app.7z");
+    let checksums = Checksums::default();
+
+    // interrupted in the middle of the second part.
+    std::fs::write(&output, "abcde").unwrap();
+    let answers = Scripted::new(["y"]);
+    let combined = combine_files(&mut parts, &output, &checksums, Instant::now(), &answers);
+    assert!(combined.unwrap().is_some());
+    assert!(answers.is_finished());
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "abcdefghi");
+
+    // a fully combined file is reused as is.
+    let answers = Scripted::new(["y"]);
+    let combined = combine_files(&mut parts, &output, &checksums, Instant::now(), &answers);
+    assert!(combined.unwrap().is_none());
+
+    // unless it does not match the checksums of the parts, then it is removed.
+    let checksum_file = dir.join("app.7z.md5");
+    std::fs::write(
+        &checksum_file,
+        "49f68a5c8493ec2c0bf489821c21fc3b  app.7z.003\n",
+    )
+    .unwrap();
+    let with_checksums = Checksums::load(std::slice::from_ref(&checksum_file), &parts).unwrap();
+    let answers = Scripted::new(["y"]);
+    let combined = combine_files(
+        &mut parts,
+        &output,
+        &with_checksums,
+        Instant::now(),
+        &answers,
+    );
+    assert!(combined.unwrap().is_none());
+
+    std::fs::write(&output, "abcdefgh!").unwrap();
+    let answers = Scripted::new(["y"]);
+    assert!(matches!(
+        combine_files(
+            &mut parts,
+            &output,
+            &with_checksums,
+            Instant::now(),
+            &answers
+        ),
+        Err(Error::Checksum(..))
+    ));
+    assert!(!output.exists());
+    std::fs::remove_file(checksum_file).unwrap();
+    std::fs::write(&output, "abcdefghi").unwrap();
+
+    // interrupted while the second part was written concurrently with the others.
+    std::fs::write(&output, "abc\0\0\0\0hi").unwrap();
+    std::fs::write(dir.join(".app.7z.combining"), "app.7z.001\napp.7z.003\n").unwrap();
+    let answers = Scripted::new(["y"]);
+    let combined = combine_files(&mut parts, &output, &checksums, Instant::now(), &answers);
+    assert!(combined.unwrap().is_some());
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "abcdefghi");
+    assert!(!dir.join(".app.7z.combining").exists());
+
+    // a file larger than the parts is never resumed.
+    std::fs::write(&output, "abcdefghijk").unwrap();
+    let answers = Scripted::new(["n"]);
+    assert!(matches!(
+        combine_files(&mut parts, &output, &checksums, Instant::now(), &answers),
+        Err(Error::Cancelled)
+    ));
+
+    // without a journal, no full size file is left to be taken as fully combined.
+    std::fs::remove_file(&output).unwrap();
+    std::fs::create_dir(dir.join(".app.7z.combining")).unwrap();
+    let answers = Scripted::new::<&str>([]);
+    assert!(matches!(
+        combine_files(&mut parts, &output, &checksums, Instant::now(), &answers),
+        Err(Error::Combine(..))
+    ));
+    assert!(!output.exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
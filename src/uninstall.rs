@@ -0,0 +1,201 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{interact::Interactor, manifest::Manifest, platform, Error, PathExt};
+
+/// What an uninstall removed, returned by [`uninstall`].
+#[derive(Clone, Debug)]
+pub struct UninstallReport {
+    pub app_name: String,
+    /// The folder the app was installed to.
+    pub destination: PathBuf,
+    /// How many of the installed files were removed.
+    pub removed_files: usize,
+    /// The total size of the removed files, as recorded when they were installed.
+    pub removed_size: u64,
+    /// How many of the installed files were already gone.
+    pub missing_files: usize,
+    /// The shortcut removed, if any.
+    pub shortcut: Option<PathBuf>,
+    /// Files in the destination that were not installed and were kept.
+    pub kept: Vec<PathBuf>,
+}
+
+/// Remove the app called `app_name` using the manifest its install wrote to `state_dir`.
+///
+/// Only the files and shortcut recorded in the manifest are removed,
+/// along with anything else inside the destination if `interactor` agrees.
+///
+/// # Errors
+///
+/// Will return `Err` if the app has no manifest, the manifest points outside of the install,
+/// the user cancels, or a file could not be removed.
+pub fn uninstall(
+    app_name: &str,
+    state_dir: &Path,
+    interactor: &dyn Interactor,
+) -> Result<UninstallReport, Error> {
+    let manifest_path = Manifest::path(state_dir, app_name);
+    if !manifest_path.is_file() {
+        return Err(Error::NotInstalled(app_name.to_string()));
+    }
+
+    let manifest = Manifest::load(&manifest_path)?;
+    let destination = &manifest.destination;
+    check_manifest(&manifest)?;
+
+    println!(
+        "{} was installed to {} ({} files).",
+        manifest.app_name,
+        platform::display_path(destination),
+        manifest.files.len()
+    );
+    if let Some(shortcut) = &manifest.shortcut {
+        println!("Shortcut: {}", platform::display_path(shortcut));
+    }
+
+    if !interactor.confirm(&format!("Uninstall {}?", manifest.app_name))? {
+        return Err(Error::Cancelled);
+    }
+
+    let mut report = UninstallReport {
+        app_name: manifest.app_name.clone(),
+        destination: destination.clone(),
+        removed_files: 0,
+        removed_size: 0,
+        missing_files: 0,
+        shortcut: None,
+        kept: Vec::new(),
+    };
+
+    if destination.is_dir() {
+        // resolve symlinks so a replaced directory cannot lead outside of the destination.
+        let root = dunce::canonicalize(destination)
+            .map_err(|err| Error::Uninstall(destination.clone(), err))?;
+
+        for file in &manifest.files {
+            let path = destination.join(&file.path);
+
+            let inside = path
+                .parent()
+                .and_then(|parent| dunce::canonicalize(parent).ok());
+            match inside {
+                Some(parent) if parent.starts_with(&root) => (),
+                Some(_) => return Err(Error::OutsideDestination(path)),
+                None => {
+                    report.missing_files += 1;
+                    continue;
+                }
+            }
+
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    report.removed_files += 1;
+                    report.removed_size += file.size;
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => report.missing_files += 1,
+                Err(err) => return Err(Error::Uninstall(path, err)),
+            }
+        }
+
+        remove_empty_dirs(destination, &mut report.kept)
+            .map_err(|err| Error::Uninstall(destination.clone(), err))?;
+
+        if !report.kept.is_empty() {
+            println!(
+                "\n{} files that were not installed are left in {}:",
+                report.kept.len(),
+                platform::display_path(destination)
+            );
+            for file in &report.kept {
+                println!("{}", platform::display_path(file));
+            }
+
+            // never assumed, these files may be the only copy of what the user saved there.
+            if interactor.confirm_untrusted("Remove them too?")? {
+                fs::remove_dir_all(destination)
+                    .map_err(|err| Error::Uninstall(destination.clone(), err))?;
+                report.kept.clear();
+            }
+        }
+    } else {
+        println!("{destination:?} does not exist, it was already removed.");
+        report.missing_files = manifest.files.len();
+    }
+
+    if let Some(shortcut) = &manifest.shortcut {
+        match fs::remove_file(shortcut) {
+            Ok(()) => report.shortcut = Some(shortcut.clone()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(Error::Uninstall(shortcut.clone(), err)),
+        }
+    }
+
+    fs::remove_file(&manifest_path).map_err(|err| Error::Uninstall(manifest_path, err))?;
+
+    Ok(report)
+}
+
+/// Make sure everything `manifest` would have removed belongs to its install.
+fn check_manifest(manifest: &Manifest) -> Result<(), Error> {
+    let destination = &manifest.destination;
+
+    // installs always go into the folder recorded, named after the app unless a profile renamed it.
+    let folder = manifest.folder.as_deref().unwrap_or(&manifest.app_name);
+    if !destination.is_absolute() || destination.lossy_file_name().as_deref() != Some(folder) {
+        return Err(Error::OutsideDestination(destination.clone()));
+    }
+
+    for file in &manifest.files {
+        if !file
+            .path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(Error::OutsideDestination(destination.join(&file.path)));
+        }
+    }
+
+    if let Some(shortcut) = &manifest.shortcut {
+        let is_shortcut = shortcut
+            .lossy_extension()
+            .is_some_and(|ext| ext == platform::SHORTCUT_EXT)
+            && shortcut.lossy_file_stem().as_deref()
+                == Some(
+                    manifest
+                        .shortcut_name
+                        .as_deref()
+                        .unwrap_or(&manifest.app_name),
+                );
+
+        if !is_shortcut {
+            return Err(Error::OutsideDestination(shortcut.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove every empty directory in `dir`, and `dir` itself if it ends up empty.
+///
+/// Anything else found is added to `kept`.
+fn remove_empty_dirs(dir: &Path, kept: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+
+        // does not follow symlinks, so a symlinked directory is kept as a file.
+        if entry.file_type()?.is_dir() {
+            remove_empty_dirs(&entry.path(), kept)?;
+        } else {
+            kept.push(entry.path());
+        }
+    }
+
+    if dir.read_dir()?.next().is_none() {
+        fs::remove_dir(dir)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::PathExt;
+
+/// How an archive was split into multiple files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeKind {
+    /// Only one file, nothing to combine.
+    Single,
+    /// Raw split files that must be concatenated, eg. `app.7z.001`, `app.7z.002`.
+    Split,
+    /// RAR volumes named `app.part1.rar`, `app.part2.rar`.
+    RarParts,
+    /// RAR volumes named `app.rar`, `app.r00`, `app.r01`.
+    RarLegacy,
+    /// Spanned zip volumes named `app.z01`, `app.z02`, `app.zip`.
+    ZipSpanned,
+}
+
+impl VolumeKind {
+    /// Returns true if the extractor must be given the first volume instead of the concatenated files.
+    #[must_use]
+    pub fn is_multi_volume(self) -> bool {
+        matches!(self, Self::RarParts | Self::RarLegacy | Self::ZipSpanned)
+    }
+}
+
+impl fmt::Display for VolumeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Single => "single file",
+            Self::Split => "split file",
+            Self::RarParts | Self::RarLegacy => "multi-volume RAR",
+            Self::ZipSpanned => "spanned zip",
+        })
+    }
+}
+
+/// A set of files making up one archive.
+#[derive(Debug)]
+pub struct VolumeSet {
+    pub kind: VolumeKind,
+    /// The files of the set. Multi-volume sets are sorted so the first volume comes first.
+    pub volumes: Vec<PathBuf>,
+}
+
+impl VolumeSet {
+    /// Detect which naming scheme `files` use.
+    ///
+    /// Files that are not part of a multi-volume set are dropped from it.
+    /// Anything that is not a multi-volume set is treated as split files, as before volume sets were detected.
+    #[must_use]
+    pub fn detect(files: Vec<PathBuf>) -> Self {
+        if files.len() == 1 {
+            return Self {
+                kind: VolumeKind::Single,
+                volumes: files,
+            };
+        }
+
+        for kind in [
+            VolumeKind::RarParts,
+            VolumeKind::RarLegacy,
+            VolumeKind::ZipSpanned,
+        ] {
+            let mut volumes: Vec<(u32, &PathBuf)> = files
+                .iter()
+                .filter_map(|file| Some((volume_number(kind, file)?, file)))
+                .collect();
+
+            // a set needs more than one volume, and exactly one first volume.
+            let firsts = volumes
+                .iter()
+                .filter(|(n, _)| *n == first_volume_number(kind))
+                .count();
+            if volumes.len() < 2 || firsts != 1 {
+                continue;
+            }
+
+            volumes.sort_by_key(|(n, _)| *n);
+
+            return Self {
+                kind,
+                volumes: volumes.into_iter().map(|(_, file)| file.clone()).collect(),
+            };
+        }
+
+        Self {
+            kind: VolumeKind::Split,
+            volumes: files,
+        }
+    }
+
+    /// The file to hand to the extractor for multi-volume sets.
+    #[must_use]
+    pub fn first(&self) -> Option<&Path> {
+        self.volumes.first().map(PathBuf::as_path)
+    }
+}
+
+/// The position of `path` in a volume set of `kind`, if it is named like one.
+fn volume_number(kind: VolumeKind, path: &Path) -> Option<u32> {
+    let ext = path.lossy_extension()?.to_lowercase();
+
+    match kind {
+        VolumeKind::RarParts => {
+            if ext != "rar" {
+                return None;
+            }
+            let stem = Path::new(path.file_stem()?)
+                .lossy_extension()?
+                .to_lowercase();
+            stem.strip_prefix("part")?.parse().ok()
+        }
+        VolumeKind::RarLegacy => match ext.as_str() {
+            // app.rar comes before app.r00
+            "rar" => Some(0),
+            ext => numbered_ext(ext, 'r').map(|n| n + 1),
+        },
+        VolumeKind::ZipSpanned => match ext.as_str() {
+            // app.zip comes last, but is what extractors open first.
+            "zip" => Some(0),
+            ext => numbered_ext(ext, 'z'),
+        },
+        VolumeKind::Single | VolumeKind::Split => None,
+    }
+}
+
+/// The number [`volume_number`] gives the first volume of a set of `kind`.
+fn first_volume_number(kind: VolumeKind) -> u32 {
+    match kind {
+        VolumeKind::RarParts => 1,
+        _ => 0,
+    }
+}
+
+/// Parse extensions like `r00` or `z01`, which start with `prefix` and have at least 2 digits.
+fn numbered_ext(ext: &str, prefix: char) -> Option<u32> {
+    let digits = ext.strip_prefix(prefix)?;
+
+    if digits.len() < 2 {
+        return None;
+    }
+
+    digits.parse().ok()
+}
+
+/// Strip the volume suffix from the file name of a multi-volume archive.
+///
+/// eg. `app.part1.rar`, `app.r00` and `app.z01` all become `app`.
+#[must_use]
+pub fn volume_stem(file_name: &str) -> Option<&str> {
+    let path = Path::new(file_name);
+    let ext = path.lossy_extension()?.to_lowercase();
+    let (stem, _) = file_name.rsplit_once('.')?;
+
+    if numbered_ext(&ext, 'r').is_some() || numbered_ext(&ext, 'z').is_some() {
+        return Some(stem);
+    }
+
+    if volume_number(VolumeKind::RarParts, path).is_some() {
+        return stem.rsplit_once('.').map(|(stem, _)| stem);
+    }
+
+    None
+}